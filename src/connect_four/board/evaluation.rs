@@ -0,0 +1,280 @@
+use super::{Board, GameResult};
+use crate::connect_four::square::Square;
+
+impl Board {
+    /// Scores this far from zero mean `eval` has seen the end of the game.
+    pub const WIN_EVAL: isize = 10_000;
+
+    // Weights of the parts of the heuristic.
+    const TWO_WEIGHT: isize = 2;
+    const THREE_WEIGHT: isize = 5;
+    const THREAT_WEIGHT: isize = 10;
+    const GOOD_THREAT_WEIGHT: isize = 30;
+    const CENTRE_WEIGHT: isize = 1;
+
+    /// Static score of the position, positive when it favours Yellow.
+    ///
+    /// Won games are scored `WIN_EVAL` less the number of discs played by the end, so quicker
+    /// wins score higher, and draws score 0. The same goes for a game the side to move wins with
+    /// its next disc, or one where the other side has two winning drops that can't both be
+    /// blocked.
+    ///
+    /// Otherwise each colour scores for every window of `connect_n` cells the opponent has no
+    /// disc in, more the fuller it is, for discs near the centre, and for threats. Threats
+    /// count extra on the rows where they decide the game once the board fills up: odd rows,
+    /// counted from 1 at the bottom, for Yellow, who moves first, and even rows for Red.
+    pub fn eval(&self) -> isize {
+        if let Some(score) = self.forced_eval() {
+            return score;
+        }
+
+        self.heuristic_eval(Square::Yellow) - self.heuristic_eval(Square::Red)
+    }
+
+    fn forced_eval(&self) -> Option<isize> {
+        let plies = self.moves_played();
+
+        match &self.result {
+            Some(GameResult::Win(winner, _)) => return Some(Self::win_eval(*winner, plies)),
+            Some(GameResult::Draw) => return Some(0),
+            None => {}
+        }
+
+        let to_move = self.side_to_move();
+        let opponent = to_move.flip_into();
+
+        if !self.winning_moves(to_move).is_empty() {
+            Some(Self::win_eval(to_move, plies + 1))
+        } else if self.winning_moves(opponent).len() > 1 {
+            Some(Self::win_eval(opponent, plies + 2))
+        } else {
+            None
+        }
+    }
+
+    fn win_eval(winner: Square, plies: usize) -> isize {
+        let score = Self::WIN_EVAL - plies as isize;
+
+        match winner {
+            Square::Red => -score,
+            _ => score,
+        }
+    }
+
+    /// How many windows of `connect_n` cells hold no opponent disc, by how many `color` discs
+    /// they hold: entry `k` counts the windows with `k` discs. A line of `connect_n` only shows
+    /// up once the game is over, in the entry past the end, which is left off.
+    pub fn open_windows(&self, color: Square) -> Vec<usize> {
+        let discs = self.color_mask(color);
+        let open = discs | self.color_mask(Square::Empty);
+        let board = self.board_mask();
+        let mut windows = vec![0; self.connect_n];
+
+        for direction in self.directions() {
+            let steps = |bits: u64, shr: bool| {
+                (0..self.connect_n).map(move |l| {
+                    let shift = (l * direction) as u32;
+                    if shr {
+                        bits.checked_shr(shift).unwrap_or(0)
+                    } else {
+                        bits.checked_shl(shift).unwrap_or(0)
+                    }
+                })
+            };
+
+            // Windows never cross the spare bits, so starting cells with every cell of the
+            // window on the board are exactly the windows that fit.
+            let mut starts = steps(board, true).fold(board, |acc, bits| acc & bits);
+
+            while starts != 0 {
+                let start = starts & starts.wrapping_neg();
+                starts &= starts - 1;
+
+                let window = steps(start, false).fold(0, |acc, bit| acc | bit);
+                if window & open != window {
+                    continue;
+                }
+
+                if let Some(count) = windows.get_mut((window & discs).count_ones() as usize) {
+                    *count += 1;
+                }
+            }
+        }
+
+        windows
+    }
+
+    fn heuristic_eval(&self, color: Square) -> isize {
+        let discs = self.color_mask(color);
+        let windows = self.open_windows(color);
+        let mut score = 0;
+
+        score += Self::THREE_WEIGHT * windows[self.connect_n - 1] as isize;
+        if self.connect_n > 2 {
+            score += Self::TWO_WEIGHT * windows[self.connect_n - 2] as isize;
+        }
+
+        for column in 0..self.width {
+            let distance = (2 * column).abs_diff(self.width - 1);
+            let bonus = ((self.width - 1 - distance) / 2) as isize;
+
+            score += Self::CENTRE_WEIGHT
+                * bonus
+                * (discs & self.column_mask(column)).count_ones() as isize;
+        }
+
+        let threats = self.threat_mask(color);
+        let good_threats = threats & self.odd_rows_mask(color != Square::Yellow);
+
+        score += Self::THREAT_WEIGHT * threats.count_ones() as isize;
+        score += Self::GOOD_THREAT_WEIGHT * good_threats.count_ones() as isize;
+
+        score
+    }
+
+    /// Cells on odd rows counted from 1 at the bottom, or on even rows if `even` is set.
+    fn odd_rows_mask(&self, even: bool) -> u64 {
+        (usize::from(even)..self.height)
+            .step_by(2)
+            .flat_map(|row| (0..self.width).map(move |column| (row, column)))
+            .fold(0, |acc, (row, column)| acc | self.cell_bit(row, column))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rstest::rstest;
+
+    /// The column whose disc `eval` likes best for the side to move.
+    fn best_by_eval(board: &Board) -> usize {
+        let color = board.side_to_move();
+
+        board
+            .list_valid_moves()
+            .into_iter()
+            .max_by_key(|column| {
+                let mut child = board.clone();
+                child.apply_move(*column, color).unwrap();
+
+                match color {
+                    Square::Red => -child.eval(),
+                    _ => child.eval(),
+                }
+            })
+            .unwrap()
+    }
+
+    #[rstest]
+    #[case::centre_opening("", 3)]
+    #[case::centre_reply("1", 3)]
+    #[case::take_the_win("121212", 0)]
+    #[case::block_vertical("12121", 0)]
+    #[case::block_horizontal("25334", 0)]
+    #[case::make_a_fork("4757", 2)]
+    fn test_eval_ranks_moves(#[case] moves: &str, #[case] expected: usize) {
+        let board = Board::from_moves(moves).unwrap();
+
+        assert_eq!(best_by_eval(&board), expected);
+    }
+
+    #[test]
+    fn test_open_windows() {
+        let board = Board::new_from_str_vec(&[
+            "_______", "_______", "_______", "_______", "_______", "YYYR___",
+        ]);
+
+        // Every window along the bottom row holds the Red disc, so each Yellow disc only
+        // counts in the window up its column and the one up the diagonal to the right.
+        assert_eq!(board.open_windows(Square::Yellow), vec![56, 6, 0, 0]);
+        assert_eq!(board.open_windows(Square::Red)[1], 4);
+        assert_eq!(
+            Board::new().open_windows(Square::Red).iter().sum::<usize>(),
+            69
+        );
+    }
+
+    #[test]
+    fn test_eval_empty() {
+        assert_eq!(Board::new().eval(), 0);
+    }
+
+    #[test]
+    fn test_eval_move_under_threat_loses() {
+        // Red to move, and a Red disc in column 5 lets Yellow win on top of it.
+        let board = Board::new_from_str_vec(&[
+            "_______", "_______", "_______", "___Y___", "__YRR__", "RYRRYYY",
+        ]);
+        assert_eq!(board.side_to_move(), Square::Red);
+
+        let mut child = board.clone();
+        child.apply_move(4, Square::Red).unwrap();
+
+        assert_eq!(child.eval(), Board::WIN_EVAL - 13);
+        assert_ne!(best_by_eval(&board), 4);
+    }
+
+    #[test]
+    fn test_eval_is_mirror_symmetric() {
+        let board = Board::from_moves("4452367").unwrap();
+
+        assert_ne!(board.eval(), 0);
+        assert_eq!(board.eval(), board.mirrored().eval());
+    }
+
+    #[test]
+    fn test_eval_wins() {
+        let quick = Board::from_moves("1212121").unwrap();
+        let slow = Board::from_moves("121367121").unwrap();
+
+        assert_eq!(quick.eval(), Board::WIN_EVAL - 7);
+        assert_eq!(slow.eval(), Board::WIN_EVAL - 9);
+
+        let red = Board::from_moves("12123262").unwrap();
+        assert_eq!(red.eval(), -(Board::WIN_EVAL - 8));
+    }
+
+    #[test]
+    fn test_eval_draw() {
+        let mut board = Board::with_dimensions(3, 2, 3).unwrap();
+        board.play_moves("123123").unwrap();
+
+        assert_eq!(board.check_for_win(), Some(GameResult::Draw));
+        assert_eq!(board.eval(), 0);
+    }
+
+    #[test]
+    fn test_eval_next_move_wins() {
+        // Yellow to move with three in a row.
+        let board = Board::from_moves("112233").unwrap();
+
+        assert_eq!(board.eval(), Board::WIN_EVAL - 7);
+    }
+
+    #[test]
+    fn test_eval_unstoppable_double_threat() {
+        // Red to move, but Yellow can win in column 2 or 6.
+        let board = Board::from_moves("33445").unwrap();
+
+        assert_eq!(board.eval(), Board::WIN_EVAL - 7);
+    }
+
+    #[test]
+    fn test_eval_threat_parity() {
+        // The same Yellow threat in column 5, on the third row or on the fourth.
+        let odd = Board::new_from_str_vec(&[
+            "_______", "_______", "_______", "_YYY___", "_RRY___", "RYRR___",
+        ]);
+        let even = Board::new_from_str_vec(&[
+            "_______", "_______", "_YYY___", "_RRY___", "_YRR___", "RYRR___",
+        ]);
+
+        assert!(odd.threats(Square::Yellow).contains(&(2, 4)));
+        assert!(even.threats(Square::Yellow).contains(&(3, 4)));
+
+        let odd_bonus = odd.heuristic_eval(Square::Yellow) - odd.heuristic_eval(Square::Red);
+        let even_bonus = even.heuristic_eval(Square::Yellow) - even.heuristic_eval(Square::Red);
+        assert!(odd_bonus - even_bonus > Board::GOOD_THREAT_WEIGHT / 2);
+    }
+}
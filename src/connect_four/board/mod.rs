@@ -0,0 +1,1292 @@
+use std::{
+    fmt::{Display, Formatter},
+    hash::{Hash, Hasher},
+};
+
+use crate::connect_four::square::Square;
+
+mod evaluation;
+mod notation;
+mod tactics;
+mod validation;
+mod zobrist;
+
+pub use notation::{MoveSequenceError, ParseError};
+pub use validation::IllegalPosition;
+
+// Dimensions of the standard game.
+const WIDTH: usize = 7;
+const HEIGHT: usize = 6;
+const CONNECT_N: usize = 4;
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum Error {
+    #[error("Column {0} is an invalid move.")]
+    InvalidMove(usize),
+    #[error("A {width}x{height} board with {connect_n} in a row to win is not supported.")]
+    InvalidDimensions {
+        width: usize,
+        height: usize,
+        connect_n: usize,
+    },
+}
+
+/// A connect four position stored as two bitboards.
+///
+/// Each column takes `height + 1` bits, and bit `column * (height + 1) + row` is the cell at
+/// `row` (counted from the bottom) of `column`. The spare bit on top of every column keeps the
+/// shifts in `has_line` from wrapping from one column into the next.
+///
+/// `mask` holds every disc and `current` holds the discs of the colour to move, which is
+/// Yellow after an even number of discs and Red after an odd number.
+///
+/// The result of the game is worked out from each new disc as it is played and cached, so
+/// `check_for_win` is free. The Zobrist keys of the position and of its mirror image are kept
+/// up to date the same way.
+#[derive(Debug, Clone)]
+pub struct Board {
+    width: usize,
+    height: usize,
+    connect_n: usize,
+    current: u64,
+    mask: u64,
+    result: Option<GameResult>,
+    key: u64,
+    mirror_key: u64,
+}
+
+/// A run of `connect_n` or more discs, as `(row, column)` cells from one end to the other.
+pub type Line = Vec<(usize, usize)>;
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum GameResult {
+    /// The winner, and every line made by their winning move.
+    Win(Square, Vec<Line>),
+    Draw,
+}
+
+impl GameResult {
+    pub fn winner(&self) -> Option<Square> {
+        match self {
+            Self::Win(color, _) => Some(*color),
+            Self::Draw => None,
+        }
+    }
+}
+
+impl Board {
+    /// The standard 7 wide, 6 high board with four in a row to win.
+    pub fn new() -> Self {
+        Self {
+            width: WIDTH,
+            height: HEIGHT,
+            connect_n: CONNECT_N,
+            current: 0,
+            mask: 0,
+            result: None,
+            key: 0,
+            mirror_key: 0,
+        }
+    }
+
+    /// An empty board of any size that fits in the bitboards, i.e. `width * (height + 1)` of
+    /// at most 64 cells, where `connect_n` in a row wins.
+    pub fn with_dimensions(width: usize, height: usize, connect_n: usize) -> Result<Self, Error> {
+        let fits = width > 0 && height > 0 && width * (height + 1) <= u64::BITS as usize;
+        let winnable = connect_n > 1 && connect_n <= width.max(height);
+
+        if !fits || !winnable {
+            return Err(Error::InvalidDimensions {
+                width,
+                height,
+                connect_n,
+            });
+        }
+
+        Ok(Self {
+            width,
+            height,
+            connect_n,
+            current: 0,
+            mask: 0,
+            result: None,
+            key: 0,
+            mirror_key: 0,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// How many discs in a row win the game.
+    pub fn connect_n(&self) -> usize {
+        self.connect_n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mask == 0
+    }
+
+    /// Yellow moves first, so it's Yellow's turn whenever there is an even number of discs.
+    pub fn side_to_move(&self) -> Square {
+        Self::color_to_move(self.mask)
+    }
+
+    /// Number of discs on the board.
+    pub fn moves_played(&self) -> usize {
+        self.mask.count_ones() as usize
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.mask == self.board_mask()
+    }
+
+    /// The discs of the side to move and every disc, as laid out above, for searches that work
+    /// on the bitboards directly.
+    pub(crate) fn bitboards(&self) -> (u64, u64) {
+        (self.current, self.mask)
+    }
+
+    /// Build a connect four board from rows given top to bottom. The dimensions come from the
+    /// rows.
+    #[cfg(test)]
+    pub fn new_from_str_vec(rows: &[&str]) -> Self {
+        let width = rows[0].len();
+        let mut board = Self::with_dimensions(width, rows.len(), CONNECT_N).unwrap();
+        let mut yellow = 0;
+
+        for (i, row) in rows.iter().rev().enumerate() {
+            for (j, square) in row.chars().enumerate() {
+                let bit = board.cell_bit(i, j);
+                match square {
+                    'R' => board.mask |= bit,
+                    'Y' => {
+                        board.mask |= bit;
+                        yellow |= bit;
+                    }
+                    _ => {}
+                };
+            }
+        }
+
+        board.set_masks(yellow, board.mask);
+        board.recompute_cached();
+        board
+    }
+
+    /// Drop a `color` disc into `column`.
+    ///
+    /// Returns the result of the game after the move if it is over. Only lines through the new
+    /// disc are checked, and once the game is over later moves don't change the result.
+    pub fn apply_move(
+        &mut self,
+        column: usize,
+        color: Square,
+    ) -> Result<Option<GameResult>, Error> {
+        if !self.is_valid_move(column) {
+            return Err(Error::InvalidMove(column));
+        }
+
+        // Adding the bottom bit carries up through the column's discs into its lowest empty cell.
+        let bit = (self.mask + self.bottom_mask(column)) & self.column_mask(column);
+
+        let mut yellow = self.color_mask(Square::Yellow);
+        if color == Square::Yellow {
+            yellow |= bit;
+        }
+
+        self.set_masks(yellow, self.mask | bit);
+        self.toggle_keys(color, bit);
+
+        if self.result.is_none() {
+            let lines = self.lines_through(self.color_mask(color), bit);
+
+            if !lines.is_empty() {
+                self.result = Some(GameResult::Win(color, lines));
+            } else if self.is_full() {
+                self.result = Some(GameResult::Draw);
+            }
+        }
+
+        Ok(self.result.clone())
+    }
+
+    pub fn remove_move(&mut self, column: usize, color: Square) -> Result<(), Error> {
+        if column > self.width - 1 {
+            return Err(Error::InvalidMove(column));
+        }
+
+        let column_discs = self.mask & self.column_mask(column);
+        if column_discs == 0 {
+            return Err(Error::InvalidMove(column));
+        }
+
+        let top = 1 << (u64::BITS - 1 - column_discs.leading_zeros());
+        if self.color_mask(color) & top == 0 {
+            return Err(Error::InvalidMove(column));
+        }
+
+        let yellow = self.color_mask(Square::Yellow) & !top;
+        self.set_masks(yellow, self.mask & !top);
+        self.toggle_keys(color, top);
+
+        // Taking a disc away can't make a new line, but it may have been part of the old one.
+        self.result = match self.result {
+            Some(GameResult::Win(..)) => self.scan_for_result(),
+            _ => None,
+        };
+
+        Ok(())
+    }
+
+    pub fn list_valid_moves(&self) -> Vec<usize> {
+        (0..self.width)
+            .filter(|column| self.can_play(*column))
+            .collect()
+    }
+
+    pub fn is_valid_move(&self, column: usize) -> bool {
+        column < self.width && self.can_play(column)
+    }
+
+    pub fn check_for_win(&self) -> Option<GameResult> {
+        self.result.clone()
+    }
+
+    /// 64-bit Zobrist key of the discs on the board. It is the same on every run, so it can be
+    /// stored.
+    pub fn key(&self) -> u64 {
+        self.key
+    }
+
+    /// The key the position would have if it were flipped left to right.
+    pub fn mirror_key(&self) -> u64 {
+        self.mirror_key
+    }
+
+    /// A key shared by the position and its mirror image. It is the key of `canonical()`.
+    pub fn canonical_key(&self) -> u64 {
+        self.key.min(self.mirror_key)
+    }
+
+    /// The position flipped left to right.
+    pub fn mirrored(&self) -> Self {
+        let column_bits = self.height + 1;
+        let mut mirrored = self.clone();
+        mirrored.current = 0;
+        mirrored.mask = 0;
+
+        for column in 0..self.width {
+            let from = column * column_bits;
+            let to = self.mirror_column(column) * column_bits;
+
+            mirrored.current |= ((self.current & self.column_mask(column)) >> from) << to;
+            mirrored.mask |= ((self.mask & self.column_mask(column)) >> from) << to;
+        }
+
+        mirrored.key = self.mirror_key;
+        mirrored.mirror_key = self.key;
+
+        if let Some(GameResult::Win(_, lines)) = &mut mirrored.result {
+            for (_, column) in lines.iter_mut().flatten() {
+                *column = self.mirror_column(*column);
+            }
+        }
+
+        mirrored
+    }
+
+    /// One fixed position out of the pair made by this position and its mirror image, and
+    /// whether it is the mirror image.
+    ///
+    /// Moves found for the canonical position are translated back with `mirror_column` when it
+    /// was flipped.
+    pub fn canonical(&self) -> (Self, bool) {
+        if self.mirror_key < self.key {
+            (self.mirrored(), true)
+        } else {
+            (self.clone(), false)
+        }
+    }
+
+    /// Where `column` ends up when the board is flipped left to right. Flipping twice gives
+    /// back the original column.
+    pub fn mirror_column(&self, column: usize) -> usize {
+        self.width - 1 - column
+    }
+
+    /// Work out the result from scratch, for boards that weren't built up move by move.
+    fn scan_for_result(&self) -> Option<GameResult> {
+        for color in [Square::Yellow, Square::Red] {
+            let discs = self.color_mask(color);
+            let mut lines: Vec<Line> = vec![];
+
+            let mut remaining = discs;
+            while remaining != 0 {
+                let bit = remaining & remaining.wrapping_neg();
+                remaining &= remaining - 1;
+
+                for line in self.lines_through(discs, bit) {
+                    if !lines.contains(&line) {
+                        lines.push(line);
+                    }
+                }
+            }
+
+            if !lines.is_empty() {
+                return Some(GameResult::Win(color, lines));
+            }
+        }
+
+        if self.is_full() {
+            return Some(GameResult::Draw);
+        }
+        None
+    }
+
+    /// Whether `discs` contains `connect_n` in a row in any direction.
+    fn has_line(&self, discs: u64) -> bool {
+        self.directions().iter().any(|shift| {
+            // Each pass keeps the discs that also have the next disc along the line.
+            let line = (1..self.connect_n).fold(discs, |line, l| {
+                line & discs.checked_shr((l * shift) as u32).unwrap_or(0)
+            });
+            line != 0
+        })
+    }
+
+    /// Bit shifts that step one cell along each kind of line.
+    fn directions(&self) -> [usize; 4] {
+        let column_bits = self.height + 1;
+
+        [
+            1,               // Vertical
+            column_bits,     // Horizontal
+            column_bits - 1, // Diagonal \
+            column_bits + 1, // Diagonal /
+        ]
+    }
+
+    /// Every run of `connect_n` or more `discs` through the disc at `bit`.
+    fn lines_through(&self, discs: u64, bit: u64) -> Vec<Line> {
+        self.directions()
+            .iter()
+            .filter_map(|shift| {
+                let shift = *shift as u32;
+
+                // Cells off the board are never set in `discs`, so both walks stop at the edges.
+                let mut start = bit;
+                while discs & start.checked_shr(shift).unwrap_or(0) != 0 {
+                    start >>= shift;
+                }
+
+                let mut length = 0;
+                let mut cell = start;
+                while discs & cell != 0 {
+                    length += 1;
+                    cell = cell.checked_shl(shift).unwrap_or(0);
+                }
+
+                if length < self.connect_n {
+                    return None;
+                }
+
+                let line = (0..length)
+                    .map(|l| self.coordinates(start << (l as u32 * shift)))
+                    .collect();
+                Some(line)
+            })
+            .collect()
+    }
+
+    /// Recompute everything that is normally updated move by move, for boards whose masks were
+    /// set directly.
+    fn recompute_cached(&mut self) {
+        self.result = self.scan_for_result();
+        self.key = 0;
+        self.mirror_key = 0;
+
+        for color in [Square::Yellow, Square::Red] {
+            let mut discs = self.color_mask(color);
+            while discs != 0 {
+                let bit = discs & discs.wrapping_neg();
+                discs &= discs - 1;
+                self.toggle_keys(color, bit);
+            }
+        }
+    }
+
+    /// Add or remove a `color` disc at `bit` from both keys.
+    fn toggle_keys(&mut self, color: Square, bit: u64) {
+        let (row, column) = self.coordinates(bit);
+        let mirror_column = self.mirror_column(column);
+
+        self.key ^= zobrist::disc_key(color, bit.trailing_zeros() as usize);
+        self.mirror_key ^= zobrist::disc_key(color, mirror_column * (self.height + 1) + row);
+    }
+
+    fn set_masks(&mut self, yellow: u64, mask: u64) {
+        self.current = match Self::color_to_move(mask) {
+            Square::Yellow => yellow,
+            _ => yellow ^ mask,
+        };
+        self.mask = mask;
+    }
+
+    fn color_to_move(mask: u64) -> Square {
+        match mask.count_ones() % 2 {
+            0 => Square::Yellow,
+            _ => Square::Red,
+        }
+    }
+
+    fn color_mask(&self, color: Square) -> u64 {
+        let to_move = Self::color_to_move(self.mask);
+
+        match color {
+            Square::Empty => self.board_mask() & !self.mask,
+            c if c == to_move => self.current,
+            _ => self.current ^ self.mask,
+        }
+    }
+
+    fn square(&self, i: usize, j: usize) -> Square {
+        let bit = self.cell_bit(i, j);
+
+        if self.mask & bit == 0 {
+            Square::Empty
+        } else if self.color_mask(Square::Yellow) & bit != 0 {
+            Square::Yellow
+        } else {
+            Square::Red
+        }
+    }
+
+    fn can_play(&self, column: usize) -> bool {
+        self.mask & self.top_mask(column) == 0
+    }
+
+    fn cell_bit(&self, i: usize, j: usize) -> u64 {
+        1 << (j * (self.height + 1) + i)
+    }
+
+    /// The `(row, column)` of a single cell bit.
+    fn coordinates(&self, bit: u64) -> (usize, usize) {
+        let index = bit.trailing_zeros() as usize;
+
+        (index % (self.height + 1), index / (self.height + 1))
+    }
+
+    fn bottom_mask(&self, column: usize) -> u64 {
+        self.cell_bit(0, column)
+    }
+
+    fn top_mask(&self, column: usize) -> u64 {
+        self.cell_bit(self.height - 1, column)
+    }
+
+    fn column_mask(&self, column: usize) -> u64 {
+        ((1 << self.height) - 1) << (column * (self.height + 1))
+    }
+
+    fn board_mask(&self) -> u64 {
+        (0..self.width).fold(0, |acc, column| acc | self.column_mask(column))
+    }
+}
+
+// The cached result is left out: it follows from the discs, except in unreachable positions
+// where both colours have a line and it depends on which was made first. The keys follow from
+// the discs too.
+impl PartialEq for Board {
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width
+            && self.height == other.height
+            && self.connect_n == other.connect_n
+            && self.current == other.current
+            && self.mask == other.mask
+    }
+}
+
+impl Eq for Board {}
+
+impl Hash for Board {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key.hash(state);
+    }
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The alternate form, `{:#}`, highlights the winning lines with ANSI reverse video.
+impl Display for Board {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let winning_cells: Vec<_> = match &self.result {
+            Some(GameResult::Win(_, lines)) if f.alternate() => lines.iter().flatten().collect(),
+            _ => vec![],
+        };
+
+        for i in (0..self.height).rev() {
+            write!(f, "{i:>2} [")?;
+            for j in 0..self.width {
+                let square = self.square(i, j);
+
+                if winning_cells.contains(&&(i, j)) {
+                    write!(f, " \x1b[7m{square}\x1b[0m")?;
+                } else {
+                    write!(f, " {square}")?;
+                }
+            }
+            writeln!(f, " ]")?;
+        }
+
+        write!(f, " x  ")?;
+        for i in 0..self.width {
+            write!(f, " {i}")?;
+        }
+        writeln!(f, "  ")?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rstest::rstest;
+
+    #[test]
+    fn test_new_from_str_vec() {
+        let data = &[
+            "_______", "_______", "_______", "_______", "_______", "_______",
+        ];
+        let board = Board::new_from_str_vec(data);
+
+        assert_eq!(board, Board::new());
+        assert!(board.is_empty());
+        assert!(!board.is_full());
+    }
+
+    #[test]
+    fn test_new_from_str_vec_with_moves() {
+        let data = &[
+            "_______", "_______", "R______", "R______", "R______", "R_YYYY_",
+        ];
+        let board = Board::new_from_str_vec(data);
+
+        let mut expected_board = Board::new();
+
+        expected_board.apply_move(0, Square::Red).unwrap();
+        expected_board.apply_move(0, Square::Red).unwrap();
+        expected_board.apply_move(0, Square::Red).unwrap();
+        expected_board.apply_move(0, Square::Red).unwrap();
+
+        expected_board.apply_move(2, Square::Yellow).unwrap();
+        expected_board.apply_move(3, Square::Yellow).unwrap();
+        expected_board.apply_move(4, Square::Yellow).unwrap();
+        expected_board.apply_move(5, Square::Yellow).unwrap();
+
+        assert_eq!(board, expected_board);
+    }
+
+    #[test]
+    fn test_apply_move() {
+        let data = &[
+            "_______", "_______", "_______", "_______", "_______", "Y______",
+        ];
+
+        let mut board = Board::new();
+
+        board.apply_move(0, Square::Yellow).unwrap();
+
+        assert_eq!(board, Board::new_from_str_vec(data));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_apply_move_fail() {
+        let data = &[
+            "Y______", "Y______", "Y______", "Y______", "Y______", "Y______",
+        ];
+
+        let mut board = Board::new_from_str_vec(data);
+
+        board.apply_move(0, Square::Yellow).unwrap();
+    }
+
+    #[test]
+    fn test_remove_move() {
+        let player_move = (0, Square::Yellow);
+        let mut board = Board::new();
+
+        board.apply_move(player_move.0, player_move.1).unwrap();
+
+        board.remove_move(player_move.0, player_move.1).unwrap();
+
+        assert_eq!(board, Board::new());
+    }
+    #[test]
+    fn test_remove_move_bound_test() {
+        let data = &[
+            "_______", "Y______", "Y______", "Y______", "Y______", "Y______",
+        ];
+        let player_move = (0, Square::Yellow);
+        let mut board = Board::new_from_str_vec(data);
+
+        board.apply_move(player_move.0, player_move.1).unwrap();
+
+        board.remove_move(player_move.0, player_move.1).unwrap();
+
+        assert_eq!(board, Board::new_from_str_vec(data));
+    }
+
+    #[rstest]
+    #[case(&["_______", "_______", "_______", "_______", "_______", "_______"], None)]
+    #[case(&["_______", "_______", "_______", "_______", "_______", "R______"], None)]
+    #[case(&["_______", "_______", "_______", "_______", "R______", "R______"], None)]
+    #[case(&["_______", "_______", "_______", "R______", "R______", "R______"], None)]
+    #[case::vert_bl(&[
+        "_______",
+        "_______",
+        "R______",
+        "R______",
+        "R______",
+        "R______"
+    ],
+    Some(GameResult::Win(Square::Red, vec![])))]
+    #[case::vert_bm(&[
+        "_______",
+        "_______",
+        "_R_____",
+        "_R_____",
+        "_R_____",
+        "_R_____",
+    ], Some(GameResult::Win(Square::Red, vec![])))]
+    #[case::vert_br(&[
+        "_______",
+        "_______",
+        "______R",
+        "______R",
+        "______R",
+        "______R",
+    ], Some(GameResult::Win(Square::Red, vec![])))]
+    #[case::vert_ml(&[
+        "_______",
+        "R______",
+        "R______",
+        "R______",
+        "R______",
+        "_______",
+    ], Some(GameResult::Win(Square::Red, vec![])))]
+    #[case::vert_mm(&[
+        "_______",
+        "_R_____",
+        "_R_____",
+        "_R_____",
+        "_R_____",
+        "_______",
+    ], Some(GameResult::Win(Square::Red, vec![])))]
+    #[case::vert_mr(&[
+        "_______",
+        "______R",
+        "______R",
+        "______R",
+        "______R",
+        "_______",
+    ], Some(GameResult::Win(Square::Red, vec![])))]
+    #[case::vert_tl(&[
+        "R______",
+        "R______",
+        "R______",
+        "R______",
+        "_______",
+        "_______",
+    ], Some(GameResult::Win(Square::Red, vec![])))]
+    #[case::vert_tm(&[
+        "_R_____",
+        "_R_____",
+        "_R_____",
+        "_R_____",
+        "_______",
+        "_______",
+    ], Some(GameResult::Win(Square::Red, vec![])))]
+    #[case::vert_tr(&[
+        "______R",
+        "______R",
+        "______R",
+        "______R",
+        "_______",
+        "_______",
+    ], Some(GameResult::Win(Square::Red, vec![])))]
+    #[case::horz_bl(&[
+        "_______",
+        "_______",
+        "_______",
+        "_______",
+        "_______",
+        "YYYY___",
+    ], Some(GameResult::Win(Square::Yellow, vec![])))]
+    #[case::horz_bm(&[
+        "_______",
+        "_______",
+        "_______",
+        "_______",
+        "_______",
+        "_YYYY__",
+    ], Some(GameResult::Win(Square::Yellow, vec![])))]
+    #[case::horz_br(&[
+        "_______",
+        "_______",
+        "_______",
+        "_______",
+        "_______",
+        "___YYYY",
+    ], Some(GameResult::Win(Square::Yellow, vec![])))]
+    #[case::horz_ml(&[
+        "_______",
+        "_______",
+        "_______",
+        "YYYY___",
+        "_______",
+        "_______",
+    ], Some(GameResult::Win(Square::Yellow, vec![])))]
+    #[case::horz_mm(&[
+        "_______",
+        "_______",
+        "_______",
+        "_YYYY__",
+        "_______",
+        "_______",
+    ], Some(GameResult::Win(Square::Yellow, vec![])))]
+    #[case::horz_mr(&[
+        "_______",
+        "_______",
+        "_______",
+        "___YYYY",
+        "_______",
+        "_______",
+    ], Some(GameResult::Win(Square::Yellow, vec![])))]
+    #[case::horz_tl(&[
+        "YYYY___",
+        "_______",
+        "_______",
+        "_______",
+        "_______",
+        "_______",
+    ], Some(GameResult::Win(Square::Yellow, vec![])))]
+    #[case::horz_tm(&[
+        "_YYYY__",
+        "_______",
+        "_______",
+        "_______",
+        "_______",
+        "_______",
+    ], Some(GameResult::Win(Square::Yellow, vec![])))]
+    #[case::horz_tr(&[
+        "___YYYY",
+        "_______",
+        "_______",
+        "_______",
+        "_______",
+        "_______",
+    ], Some(GameResult::Win(Square::Yellow, vec![])))]
+    #[case::sw_b(&[
+        "_______",
+        "_______",
+        "___Y___",
+        "__Y____",
+        "_Y_____",
+        "Y______",
+    ], Some(GameResult::Win(Square::Yellow, vec![])))]
+    #[case::sw_t(&[
+        "___Y___",
+        "__Y____",
+        "_Y_____",
+        "Y______",
+        "_______",
+        "_______",
+    ], Some(GameResult::Win(Square::Yellow, vec![])))]
+    #[case::ne_b(&[
+        "_______",
+        "_______",
+        "______Y",
+        "_____Y_",
+        "____Y__",
+        "___Y___",
+    ], Some(GameResult::Win(Square::Yellow, vec![])))]
+    #[case::ne_t(&[
+        "______Y",
+        "_____Y_",
+        "____Y__",
+        "___Y___",
+        "_______",
+        "_______",
+    ], Some(GameResult::Win(Square::Yellow, vec![])))]
+    #[case::se_t(&[
+        "Y______",
+        "_Y_____",
+        "__Y____",
+        "___Y___",
+        "_______",
+        "_______",
+    ], Some(GameResult::Win(Square::Yellow, vec![])))]
+    #[case::se_b(&[
+        "_______",
+        "_______",
+        "Y______",
+        "_Y_____",
+        "__Y____",
+        "___Y___",
+    ], Some(GameResult::Win(Square::Yellow, vec![])))]
+    #[case::nw_b(&[
+        "_______",
+        "_______",
+        "___Y___",
+        "____Y__",
+        "_____Y_",
+        "______Y",
+    ], Some(GameResult::Win(Square::Yellow, vec![])))]
+    #[case::nw_t(&[
+        "___Y___",
+        "____Y__",
+        "_____Y_",
+        "______Y",
+        "_______",
+        "_______",
+    ], Some(GameResult::Win(Square::Yellow, vec![])))]
+    #[case::draw(&[
+        "RYRYRYR",
+        "RYRYRYR",
+        "RYRYRYR",
+        "YRYRYRY",
+        "YRYRYRY",
+        "YRYRYRY",
+    ], Some(GameResult::Draw))]
+    fn test_check_for_win(#[case] data: &[&str; HEIGHT], #[case] expected: Option<GameResult>) {
+        let board = Board::new_from_str_vec(data);
+
+        // The lines are covered by `test_winning_lines`.
+        let test = board.check_for_win().map(|result| match result {
+            GameResult::Win(color, _) => GameResult::Win(color, vec![]),
+            draw => draw,
+        });
+
+        assert_eq!(test, expected);
+    }
+
+    #[test]
+    #[allow(clippy::bool_assert_comparison)]
+    fn test_is_full() {
+        let board = Board::new_from_str_vec(&[
+            "_______", "_______", "_______", "_______", "_______", "YY_____",
+        ]);
+
+        assert_eq!(board.is_full(), false);
+
+        let board = Board::new_from_str_vec(&[
+            "YYYYYYY", "YYYYYYY", "YYYYYYY", "YYYYYYY", "YYYYYYY", "YYYYYYY",
+        ]);
+
+        assert_eq!(board.is_full(), true);
+    }
+
+    #[test]
+    fn test_remove_move_wrong_color() {
+        let mut board = Board::new();
+
+        board.apply_move(3, Square::Yellow).unwrap();
+        board.apply_move(3, Square::Red).unwrap();
+
+        assert!(board.remove_move(3, Square::Yellow).is_err());
+        assert!(board.remove_move(4, Square::Yellow).is_err());
+        assert!(board.remove_move(WIDTH, Square::Yellow).is_err());
+
+        board.remove_move(3, Square::Red).unwrap();
+        board.remove_move(3, Square::Yellow).unwrap();
+
+        assert_eq!(board, Board::new());
+    }
+
+    #[test]
+    fn test_is_valid_move_out_of_bounds() {
+        let board = Board::new();
+
+        assert!(board.is_valid_move(WIDTH - 1));
+        assert!(!board.is_valid_move(WIDTH));
+    }
+
+    #[test]
+    fn test_no_win_across_columns() {
+        // Without the spare bit on top of each column, the bottom of column 1 would sit
+        // directly above the top of column 0.
+        let board = Board::new_from_str_vec(&[
+            "Y______", "Y______", "Y______", "_______", "_______", "_Y_____",
+        ]);
+
+        assert_eq!(board.check_for_win(), None);
+    }
+
+    #[test]
+    fn test_display() {
+        let mut board = Board::new();
+
+        board.apply_move(0, Square::Yellow).unwrap();
+        board.apply_move(0, Square::Red).unwrap();
+        board.apply_move(6, Square::Yellow).unwrap();
+
+        let expected = [
+            " 5 [ _ _ _ _ _ _ _ ]",
+            " 4 [ _ _ _ _ _ _ _ ]",
+            " 3 [ _ _ _ _ _ _ _ ]",
+            " 2 [ _ _ _ _ _ _ _ ]",
+            " 1 [ R _ _ _ _ _ _ ]",
+            " 0 [ Y _ _ _ _ _ Y ]",
+            " x   0 1 2 3 4 5 6  ",
+            "",
+        ]
+        .join("\n");
+
+        assert_eq!(board.to_string(), expected);
+    }
+
+    #[rstest]
+    #[case::small(6, 5, 4)]
+    #[case::large(8, 7, 4)]
+    #[case::connect_three(5, 4, 3)]
+    #[case::five_in_a_row(9, 6, 5)]
+    fn test_with_dimensions(#[case] width: usize, #[case] height: usize, #[case] connect_n: usize) {
+        let mut board = Board::with_dimensions(width, height, connect_n).unwrap();
+
+        assert_eq!(board.list_valid_moves(), (0..width).collect::<Vec<_>>());
+        assert!(!board.is_valid_move(width));
+
+        for _ in 0..height {
+            board.apply_move(width - 1, Square::Red).unwrap();
+        }
+        assert!(!board.is_valid_move(width - 1));
+
+        let rows = board.to_string().lines().count();
+        assert_eq!(rows, height + 1);
+    }
+
+    #[rstest]
+    #[case::too_many_cells(8, 8, 4)]
+    #[case::no_columns(0, 6, 4)]
+    #[case::no_rows(7, 0, 4)]
+    #[case::unwinnable(4, 3, 5)]
+    #[case::connect_one(7, 6, 1)]
+    fn test_with_dimensions_fail(
+        #[case] width: usize,
+        #[case] height: usize,
+        #[case] connect_n: usize,
+    ) {
+        assert!(Board::with_dimensions(width, height, connect_n).is_err());
+    }
+
+    #[test]
+    fn test_connect_three() {
+        let mut board = Board::with_dimensions(5, 4, 3).unwrap();
+
+        board.apply_move(1, Square::Yellow).unwrap();
+        board.apply_move(2, Square::Yellow).unwrap();
+        assert_eq!(board.check_for_win(), None);
+
+        board.apply_move(3, Square::Yellow).unwrap();
+        assert_eq!(
+            board.check_for_win().unwrap().winner(),
+            Some(Square::Yellow)
+        );
+    }
+
+    #[test]
+    fn test_five_in_a_row() {
+        let mut board = Board::with_dimensions(9, 6, 5).unwrap();
+
+        for column in 4..8 {
+            board.apply_move(column, Square::Red).unwrap();
+        }
+        assert_eq!(board.check_for_win(), None);
+
+        board.apply_move(8, Square::Red).unwrap();
+        assert_eq!(board.check_for_win().unwrap().winner(), Some(Square::Red));
+    }
+
+    #[test]
+    fn test_draw_on_small_board() {
+        let board = Board::new_from_str_vec(&["YRYR", "YRYR", "RYRY"]);
+
+        assert_eq!(board.width(), 4);
+        assert_eq!(board.height(), 3);
+        assert_eq!(board.check_for_win(), Some(GameResult::Draw));
+    }
+
+    #[test]
+    fn test_apply_move_reports_result() {
+        let mut board = Board::new();
+
+        for column in 0..3 {
+            assert_eq!(board.apply_move(column, Square::Yellow).unwrap(), None);
+            assert_eq!(board.apply_move(column, Square::Red).unwrap(), None);
+        }
+
+        assert_eq!(
+            board.apply_move(3, Square::Yellow).unwrap(),
+            Some(GameResult::Win(
+                Square::Yellow,
+                vec![vec![(0, 0), (0, 1), (0, 2), (0, 3)]]
+            ))
+        );
+        assert_eq!(
+            board.check_for_win().unwrap().winner(),
+            Some(Square::Yellow)
+        );
+
+        // Playing on doesn't change who won.
+        board.apply_move(3, Square::Red).unwrap();
+        assert_eq!(
+            board.check_for_win().unwrap().winner(),
+            Some(Square::Yellow)
+        );
+
+        board.remove_move(3, Square::Red).unwrap();
+        board.remove_move(3, Square::Yellow).unwrap();
+        assert_eq!(board.check_for_win(), None);
+    }
+
+    #[test]
+    fn test_apply_move_matches_full_scan() {
+        // The incremental result has to agree with a full rescan after every move.
+        let mut board = Board::new();
+        let mut color = Square::Yellow;
+
+        for column in [3, 2, 4, 3, 1, 5, 6, 0, 2, 4, 3, 5, 1, 4, 0, 6, 2, 3] {
+            let result = board.apply_move(column, color).unwrap();
+
+            assert_eq!(result, board.scan_for_result());
+            if result.is_some() {
+                break;
+            }
+            color.flip();
+        }
+    }
+
+    #[test]
+    fn test_apply_move_draw() {
+        let mut board = Board::new_from_str_vec(&[
+            "_YRYRYR", "RYRYRYR", "RYRYRYR", "YRYRYRY", "YRYRYRY", "YRYRYRY",
+        ]);
+
+        assert_eq!(board.check_for_win(), None);
+        assert_eq!(
+            board.apply_move(0, Square::Red).unwrap(),
+            Some(GameResult::Draw)
+        );
+    }
+
+    #[rstest]
+    #[case::vertical(&[
+        "_______",
+        "_______",
+        "_R_____",
+        "_R_____",
+        "_R_____",
+        "_R_____",
+    ], vec![vec![(0, 1), (1, 1), (2, 1), (3, 1)]])]
+    #[case::diagonal(&[
+        "_______",
+        "_______",
+        "___R___",
+        "__R____",
+        "_R_____",
+        "R______",
+    ], vec![vec![(0, 0), (1, 1), (2, 2), (3, 3)]])]
+    #[case::anti_diagonal(&[
+        "_______",
+        "_______",
+        "R______",
+        "_R_____",
+        "__R____",
+        "___R___",
+    ], vec![vec![(3, 0), (2, 1), (1, 2), (0, 3)]])]
+    #[case::five(&[
+        "_______",
+        "_______",
+        "_______",
+        "_______",
+        "_______",
+        "_RRRRR_",
+    ], vec![vec![(0, 1), (0, 2), (0, 3), (0, 4), (0, 5)]])]
+    #[case::two_lines(&[
+        "_______",
+        "_______",
+        "RRRR___",
+        "R______",
+        "R______",
+        "R______",
+    ], vec![
+        vec![(0, 0), (1, 0), (2, 0), (3, 0)],
+        vec![(3, 0), (3, 1), (3, 2), (3, 3)],
+    ])]
+    fn test_winning_lines(#[case] data: &[&str; HEIGHT], #[case] expected: Vec<Line>) {
+        let board = Board::new_from_str_vec(data);
+
+        assert_eq!(
+            board.check_for_win(),
+            Some(GameResult::Win(Square::Red, expected))
+        );
+    }
+
+    #[test]
+    fn test_winning_move_makes_two_lines() {
+        let mut board = Board::new_from_str_vec(&[
+            "_______", "_______", "_RRR___", "R______", "R______", "R______",
+        ]);
+
+        let result = board.apply_move(0, Square::Red).unwrap();
+
+        assert_eq!(
+            result,
+            Some(GameResult::Win(
+                Square::Red,
+                vec![
+                    vec![(0, 0), (1, 0), (2, 0), (3, 0)],
+                    vec![(3, 0), (3, 1), (3, 2), (3, 3)],
+                ]
+            ))
+        );
+    }
+
+    #[test]
+    fn test_display_highlights_winning_line() {
+        let board = Board::new_from_str_vec(&[
+            "_______", "_______", "_______", "_______", "RRR____", "YYYY___",
+        ]);
+
+        let highlighted = format!("{board:#}");
+        let bottom_row = highlighted.lines().nth(5).unwrap();
+
+        assert_eq!(bottom_row.matches("\x1b[7mY\x1b[0m").count(), 4);
+        assert!(!highlighted.lines().nth(4).unwrap().contains('\x1b'));
+        // The plain form is unchanged.
+        assert!(!board.to_string().contains('\x1b'));
+    }
+
+    #[test]
+    fn test_key_is_updated_incrementally() {
+        let mut board = Board::new();
+
+        board.apply_move(3, Square::Yellow).unwrap();
+        board.apply_move(4, Square::Red).unwrap();
+        board.apply_move(3, Square::Yellow).unwrap();
+
+        let mut from_scratch = board.clone();
+        from_scratch.recompute_cached();
+        assert_eq!(board.key(), from_scratch.key());
+        assert_eq!(board.mirror_key(), from_scratch.mirror_key());
+
+        board.remove_move(3, Square::Yellow).unwrap();
+        board.remove_move(4, Square::Red).unwrap();
+        board.remove_move(3, Square::Yellow).unwrap();
+        assert_eq!(board.key(), 0);
+        assert_eq!(board.mirror_key(), 0);
+    }
+
+    #[test]
+    fn test_key_transpositions() {
+        let one_way = Board::from_moves("4453").unwrap();
+        let other_way = Board::from_moves("5344").unwrap();
+
+        assert_eq!(one_way, other_way);
+        assert_eq!(one_way.key(), other_way.key());
+        assert_ne!(one_way.key(), Board::from_moves("4435").unwrap().key());
+    }
+
+    #[test]
+    fn test_key_is_stable() {
+        // Keys are stored on disk, so they must never change.
+        assert_eq!(Board::new().key(), 0);
+        assert_eq!(Board::from_moves("4").unwrap().key(), 0x3f74_3404_fb8b_dbba);
+    }
+
+    #[test]
+    fn test_mirror_key() {
+        let left = Board::from_moves("1123").unwrap();
+        let right = Board::from_moves("7765").unwrap();
+
+        assert_eq!(left.mirror_key(), right.key());
+        assert_eq!(left.key(), right.mirror_key());
+        assert_eq!(left.canonical_key(), right.canonical_key());
+        assert_ne!(left.key(), right.key());
+
+        // Symmetric positions are their own mirror image.
+        let centre = Board::from_moves("4").unwrap();
+        assert_eq!(centre.key(), centre.mirror_key());
+    }
+
+    #[test]
+    fn test_mirrored() {
+        let board = Board::from_moves("1123").unwrap();
+        let mirrored = board.mirrored();
+
+        assert_eq!(mirrored, Board::from_moves("7765").unwrap());
+        assert_eq!(mirrored.key(), board.mirror_key());
+        assert_eq!(mirrored.side_to_move(), board.side_to_move());
+        assert_eq!(mirrored.mirrored(), board);
+    }
+
+    #[test]
+    fn test_mirrored_on_other_dimensions() {
+        let mut board = Board::with_dimensions(6, 5, 4).unwrap();
+        board.play_moves("1126").unwrap();
+
+        let mut expected = Board::with_dimensions(6, 5, 4).unwrap();
+        expected.play_moves("6651").unwrap();
+
+        assert_eq!(board.mirrored(), expected);
+        assert_eq!(board.mirrored().key(), expected.key());
+    }
+
+    #[test]
+    fn test_mirrored_result() {
+        let board = Board::from_moves("1212121").unwrap();
+
+        assert_eq!(
+            board.mirrored().check_for_win(),
+            Board::from_moves("7676767").unwrap().check_for_win()
+        );
+    }
+
+    #[test]
+    fn test_canonical() {
+        let left = Board::from_moves("1123").unwrap();
+        let right = Board::from_moves("7765").unwrap();
+
+        let (left_canonical, left_flipped) = left.canonical();
+        let (right_canonical, right_flipped) = right.canonical();
+
+        assert_eq!(left_canonical, right_canonical);
+        assert_ne!(left_flipped, right_flipped);
+        assert_eq!(left_canonical.key(), left.canonical_key());
+
+        let centre = Board::from_moves("44").unwrap();
+        assert_eq!(centre.canonical(), (centre.clone(), false));
+    }
+
+    #[test]
+    fn test_mirror_column() {
+        let board = Board::new();
+
+        assert_eq!(board.mirror_column(0), 6);
+        assert_eq!(board.mirror_column(3), 3);
+        assert_eq!(board.mirror_column(board.mirror_column(2)), 2);
+    }
+}
@@ -0,0 +1,375 @@
+use std::str::FromStr;
+
+use super::{Board, IllegalPosition, CONNECT_N};
+use crate::connect_four::{board, square::Square};
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum MoveSequenceError {
+    #[error("Ply {ply}: \"{found}\" is not a column. Expected a number from 1.")]
+    NotAColumn { ply: usize, found: String },
+    #[error("Ply {ply}: column {column} can't be played.")]
+    IllegalMove { ply: usize, column: usize },
+    #[error("Ply {ply}: the game was already over.")]
+    GameOver { ply: usize },
+}
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    #[error("Expected a grid and the side to move.")]
+    MissingField,
+    #[error("Unexpected \"{0}\" after the position.")]
+    TrailingInput(String),
+    #[error("Every row of the grid must be the same length.")]
+    RaggedRows,
+    #[error("'{0}' is not a square. Expected Y, R or _.")]
+    InvalidCharacter(char),
+    #[error("\"{0}\" is not a side to move. Expected Y or R.")]
+    InvalidSideToMove(String),
+    #[error("\"{0}\" is not a number.")]
+    InvalidNumber(String),
+    #[error(transparent)]
+    Illegal(#[from] IllegalPosition),
+    #[error("The discs say {expected:?} is to move, not {found:?}.")]
+    WrongSideToMove { expected: Square, found: Square },
+    #[error("The discs say this is move {expected}, not {found}.")]
+    WrongMoveNumber { expected: usize, found: usize },
+    #[error(transparent)]
+    Dimensions(#[from] board::Error),
+}
+
+/// A one line text format for positions.
+///
+/// ```text
+/// <grid> <side to move> [<move number> [<connect n>]]
+/// ```
+///
+/// The grid lists the rows from top to bottom, separated by `/`, using the same `Y`, `R` and
+/// `_` letters as `Board`'s `Display`. The board's width and height come from the grid. The
+/// side to move is `Y` or `R`. The move number is the number of the move about to be played,
+/// starting at 1 on an empty board, and the connect length defaults to 4. For example, after
+/// Yellow opens in the centre of a standard board:
+///
+/// ```text
+/// _______/_______/_______/_______/_______/___Y___ R 2
+/// ```
+///
+/// Only positions that pass `Board::validate` parse, and the side to move and move number have
+/// to agree with the discs.
+impl FromStr for Board {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<_> = s.split_whitespace().collect();
+
+        let (grid, side_to_move) = match fields[..] {
+            [grid, side_to_move, ..] => (grid, side_to_move),
+            _ => return Err(ParseError::MissingField),
+        };
+        let move_number = fields.get(2).map(|n| parse_number(n)).transpose()?;
+        let connect_n = fields.get(3).map(|n| parse_number(n)).transpose()?;
+
+        if fields.len() > 4 {
+            return Err(ParseError::TrailingInput(fields[4..].join(" ")));
+        }
+
+        let rows: Vec<_> = grid.split('/').collect();
+        let width = rows[0].chars().count();
+
+        if rows.iter().any(|row| row.chars().count() != width) {
+            return Err(ParseError::RaggedRows);
+        }
+
+        let mut board = Board::with_dimensions(width, rows.len(), connect_n.unwrap_or(CONNECT_N))?;
+        let mut yellow = 0;
+        let mut mask = 0;
+
+        for (i, row) in rows.iter().rev().enumerate() {
+            for (j, letter) in row.chars().enumerate() {
+                let bit = board.cell_bit(i, j);
+
+                match Square::try_from(letter).map_err(ParseError::InvalidCharacter)? {
+                    Square::Yellow => yellow |= bit,
+                    Square::Red => {}
+                    Square::Empty => continue,
+                }
+                mask |= bit;
+            }
+        }
+
+        board.set_masks(yellow, mask);
+        board.validate()?;
+
+        let found = match side_to_move {
+            "Y" => Square::Yellow,
+            "R" => Square::Red,
+            _ => return Err(ParseError::InvalidSideToMove(side_to_move.to_string())),
+        };
+        let expected = board.side_to_move();
+
+        if found != expected {
+            return Err(ParseError::WrongSideToMove { expected, found });
+        }
+
+        let expected = board.moves_played() + 1;
+
+        if let Some(found) = move_number.filter(|found| *found != expected) {
+            return Err(ParseError::WrongMoveNumber { expected, found });
+        }
+
+        board.recompute_cached();
+
+        Ok(board)
+    }
+}
+
+impl Board {
+    /// A standard board after playing `moves`, a string of 1-based column digits such as
+    /// `"4453"`, the way online solvers and test suites write positions.
+    pub fn from_moves(moves: &str) -> Result<Self, MoveSequenceError> {
+        let mut board = Self::new();
+        board.play_moves(moves)?;
+
+        Ok(board)
+    }
+
+    /// Play each 1-based column digit of `moves` in turn, starting with the side to move.
+    ///
+    /// Boards more than nine columns wide have two digit columns, so there the columns are
+    /// separated by commas, as in `"10,1,10"`. Commas can separate them on any board.
+    ///
+    /// Errors give the ply, counted from 1 at the start of `moves`, that couldn't be played.
+    /// The moves before it stay on the board.
+    pub fn play_moves(&mut self, moves: &str) -> Result<(), MoveSequenceError> {
+        let columns: Vec<_> = if self.width > 9 || moves.contains(',') {
+            moves
+                .split(',')
+                .filter(|column| !column.is_empty())
+                .collect()
+        } else {
+            moves.matches(|_| true).collect()
+        };
+
+        for (i, found) in columns.into_iter().enumerate() {
+            let ply = i + 1;
+
+            let column = match found.parse() {
+                Ok(column) if column > 0 => column,
+                _ => {
+                    let found = found.to_string();
+                    return Err(MoveSequenceError::NotAColumn { ply, found });
+                }
+            };
+
+            if self.result.is_some() {
+                return Err(MoveSequenceError::GameOver { ply });
+            }
+
+            self.apply_move(column - 1, self.side_to_move())
+                .map_err(|_| MoveSequenceError::IllegalMove { ply, column })?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the position in the format `Board::from_str` parses, always including the move
+    /// number.
+    pub fn to_notation(&self) -> String {
+        let rows: Vec<String> = (0..self.height)
+            .rev()
+            .map(|i| {
+                (0..self.width)
+                    .map(|j| self.square(i, j).to_string())
+                    .collect()
+            })
+            .collect();
+
+        let side_to_move = Self::color_to_move(self.mask);
+        let move_number = self.mask.count_ones() + 1;
+
+        let mut notation = format!("{} {side_to_move} {move_number}", rows.join("/"));
+        if self.connect_n != CONNECT_N {
+            notation.push_str(&format!(" {}", self.connect_n));
+        }
+
+        notation
+    }
+}
+
+fn parse_number(field: &str) -> Result<usize, ParseError> {
+    field
+        .parse()
+        .map_err(|_| ParseError::InvalidNumber(field.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rstest::rstest;
+
+    #[test]
+    fn test_from_moves() {
+        let board = Board::from_moves("4453").unwrap();
+
+        assert_eq!(
+            board.to_notation(),
+            "_______/_______/_______/_______/___R___/__RYY__ Y 5"
+        );
+        assert_eq!(Board::from_moves("").unwrap(), Board::new());
+    }
+
+    #[test]
+    fn test_play_moves_continues_position() {
+        let mut board = Board::from_moves("44").unwrap();
+
+        board.play_moves("53").unwrap();
+
+        assert_eq!(board, Board::from_moves("4453").unwrap());
+    }
+
+    #[test]
+    fn test_play_moves_with_commas() {
+        assert_eq!(
+            Board::from_moves("4,4,5,3").unwrap(),
+            Board::from_moves("4453").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_play_moves_on_wide_board() {
+        let mut board = Board::with_dimensions(10, 5, 4).unwrap();
+
+        board.play_moves("10").unwrap();
+        board.play_moves("1,10,2").unwrap();
+
+        assert_eq!(
+            board.to_notation(),
+            "__________/__________/__________/_________Y/RR_______Y Y 5"
+        );
+    }
+
+    #[test]
+    fn test_from_moves_win() {
+        let board = Board::from_moves("1212121").unwrap();
+
+        assert_eq!(
+            board.check_for_win().unwrap().winner(),
+            Some(Square::Yellow)
+        );
+    }
+
+    #[rstest]
+    #[case::zero("440", MoveSequenceError::NotAColumn { ply: 3, found: "0".to_string() })]
+    #[case::letter("4a", MoveSequenceError::NotAColumn { ply: 2, found: "a".to_string() })]
+    #[case::sign("4+5", MoveSequenceError::NotAColumn { ply: 2, found: "+".to_string() })]
+    #[case::separated_letter("4,x", MoveSequenceError::NotAColumn {
+        ply: 2,
+        found: "x".to_string(),
+    })]
+    #[case::off_board("448", MoveSequenceError::IllegalMove { ply: 3, column: 8 })]
+    #[case::full_column("4444444", MoveSequenceError::IllegalMove { ply: 7, column: 4 })]
+    #[case::game_over("12121212", MoveSequenceError::GameOver { ply: 8 })]
+    fn test_from_moves_fail(#[case] moves: &str, #[case] expected: MoveSequenceError) {
+        assert_eq!(Board::from_moves(moves).unwrap_err(), expected);
+    }
+
+    #[test]
+    fn test_empty_board() {
+        let board: Board = "_______/_______/_______/_______/_______/_______ Y 1"
+            .parse()
+            .unwrap();
+
+        assert_eq!(board, Board::new());
+        assert_eq!(
+            board.to_notation(),
+            "_______/_______/_______/_______/_______/_______ Y 1"
+        );
+    }
+
+    #[test]
+    fn test_parse_position() {
+        let board: Board = "_______/_______/_______/___R___/___Y___/__YRY__ R"
+            .parse()
+            .unwrap();
+
+        let mut expected = Board::new();
+        expected.apply_move(2, Square::Yellow).unwrap();
+        expected.apply_move(3, Square::Red).unwrap();
+        expected.apply_move(3, Square::Yellow).unwrap();
+        expected.apply_move(3, Square::Red).unwrap();
+        expected.apply_move(4, Square::Yellow).unwrap();
+
+        assert_eq!(board, expected);
+    }
+
+    #[rstest]
+    #[case::standard("_______/_______/_______/_______/_______/___Y___ R 2")]
+    #[case::mid_game("_______/_______/___R___/__YY___/__RYR__/_YRYR__ Y 11")]
+    #[case::won("_______/_______/Y______/YR_____/YR_____/YR_____ R 8")]
+    #[case::small("_____/_____/__Y__/_RY__ R 4 3")]
+    #[case::five_in_a_row("_________/_________/_________/_________/_________/___RYY___ R 4 5")]
+    fn test_round_trip(#[case] notation: &str) {
+        let board: Board = notation.parse().unwrap();
+
+        assert_eq!(board.to_notation(), notation);
+        assert_eq!(board.to_notation().parse::<Board>().unwrap(), board);
+    }
+
+    #[test]
+    fn test_result_is_restored() {
+        let board: Board = "_______/_______/Y______/YR_____/YR_____/YR_____ R 8"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            board.check_for_win().unwrap().winner(),
+            Some(Square::Yellow)
+        );
+    }
+
+    #[test]
+    fn test_dimensions_come_from_grid() {
+        let board: Board = "____/____/____ Y".parse().unwrap();
+
+        assert_eq!(board.width(), 4);
+        assert_eq!(board.height(), 3);
+        assert_eq!(board.connect_n(), 4);
+    }
+
+    #[rstest]
+    #[case::empty("", ParseError::MissingField)]
+    #[case::no_side("_______/_______", ParseError::MissingField)]
+    #[case::trailing("____/____ Y 1 4 extra", ParseError::TrailingInput("extra".to_string()))]
+    #[case::ragged("____/___ Y", ParseError::RaggedRows)]
+    #[case::bad_character("____/_x__ Y", ParseError::InvalidCharacter('x'))]
+    #[case::bad_side("____/____ B", ParseError::InvalidSideToMove("B".to_string()))]
+    #[case::bad_move_number("____/____ Y one", ParseError::InvalidNumber("one".to_string()))]
+    #[case::floating("____/_Y__/_R__/____ Y", ParseError::Illegal(
+        IllegalPosition::FloatingDisc { row: 1, column: 1 }
+    ))]
+    #[case::too_many_red("____/R___ Y", ParseError::Illegal(
+        IllegalPosition::ImpossibleCounts { yellow: 0, red: 1 }
+    ))]
+    #[case::played_after_win("_____/Y____/YR___/YR___/YRR__ Y", ParseError::Illegal(
+        IllegalPosition::PlayedAfterGameOver { winner: Square::Yellow }
+    ))]
+    #[case::wrong_side("____/Y___ Y", ParseError::WrongSideToMove {
+        expected: Square::Red,
+        found: Square::Yellow,
+    })]
+    #[case::wrong_move_number("____/YR__ Y 5", ParseError::WrongMoveNumber {
+        expected: 3,
+        found: 5,
+    })]
+    #[case::dimensions(
+        "________/________/________/________/________/________/________/________ Y",
+        ParseError::Dimensions(board::Error::InvalidDimensions {
+            width: 8,
+            height: 8,
+            connect_n: 4,
+        })
+    )]
+    fn test_parse_fail(#[case] notation: &str, #[case] expected: ParseError) {
+        assert_eq!(notation.parse::<Board>().unwrap_err(), expected);
+    }
+}
@@ -0,0 +1,208 @@
+use super::Board;
+use crate::connect_four::square::Square;
+
+/// Questions about the next move that are answered straight from the bitboards, without
+/// trying each move on a copy of the board.
+///
+/// The move queries return columns in ascending order and are empty once the game is over.
+impl Board {
+    /// Empty cells, as `(row, column)`, where a `color` disc would complete a line. Cells that
+    /// can't be played yet because they have nothing under them are included.
+    pub fn threats(&self, color: Square) -> Vec<(usize, usize)> {
+        let mut threats = self.threat_mask(color);
+        let mut cells = vec![];
+
+        while threats != 0 {
+            let bit = threats & threats.wrapping_neg();
+            threats &= threats - 1;
+
+            cells.push(self.coordinates(bit));
+        }
+
+        cells.sort_by_key(|(row, column)| (*column, *row));
+        cells
+    }
+
+    /// Columns where `color` wins by dropping a disc.
+    pub fn winning_moves(&self, color: Square) -> Vec<usize> {
+        self.columns_of(self.playable_mask() & self.threat_mask(color))
+    }
+
+    /// Columns where `color` takes away a cell the opponent would win on next move.
+    pub fn moves_that_block(&self, color: Square) -> Vec<usize> {
+        self.columns_of(self.playable_mask() & self.threat_mask(color.flip_into()))
+    }
+
+    /// Columns where a `color` disc lands right under a cell the opponent would win on, handing
+    /// it to them. Moves that win straight away are left out.
+    pub fn moves_that_lose_immediately(&self, color: Square) -> Vec<usize> {
+        let playable = self.playable_mask();
+        let under_threats = (self.threat_mask(color.flip_into()) >> 1) & playable;
+
+        self.columns_of(under_threats & !self.threat_mask(color))
+    }
+
+    /// Every empty cell that would complete a line for `color`.
+    pub(super) fn threat_mask(&self, color: Square) -> u64 {
+        if color == Square::Empty {
+            return 0;
+        }
+
+        let discs = self.color_mask(color);
+        let shift = |step: isize| {
+            if step >= 0 {
+                discs.checked_shl(step as u32).unwrap_or(0)
+            } else {
+                discs.checked_shr(-step as u32).unwrap_or(0)
+            }
+        };
+
+        let mut threats = 0;
+        for direction in self.directions() {
+            let direction = direction as isize;
+
+            // The empty cell can be at any place along the line, with the rest of the line's
+            // discs on either side of it.
+            for gap in 0..self.connect_n as isize {
+                threats |= (0..self.connect_n as isize)
+                    .filter(|l| *l != gap)
+                    .fold(u64::MAX, |line, l| line & shift((gap - l) * direction));
+            }
+        }
+
+        threats & self.color_mask(Square::Empty)
+    }
+
+    /// The lowest empty cell of every column that isn't full.
+    fn playable_mask(&self) -> u64 {
+        if self.result.is_some() {
+            return 0;
+        }
+
+        let bottom = (0..self.width).fold(0, |acc, column| acc | self.bottom_mask(column));
+        (self.mask + bottom) & self.board_mask()
+    }
+
+    fn columns_of(&self, cells: u64) -> Vec<usize> {
+        (0..self.width)
+            .filter(|column| cells & self.column_mask(*column) != 0)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::horizontal(
+        &["_______", "_______", "_______", "_______", "RR_____", "YYY____"],
+        vec![3]
+    )]
+    #[case::gap(
+        &["_______", "_______", "_______", "_______", "RR_____", "YY_Y___"],
+        vec![2]
+    )]
+    #[case::vertical_and_horizontal(
+        &["_______", "_______", "______Y", "R_____Y", "R_____Y", "RYYY_RR"],
+        vec![4, 6]
+    )]
+    #[case::not_yet_playable(
+        &["_______", "_______", "_______", "___Y___", "__YR___", "RYRRY__"],
+        vec![]
+    )]
+    #[case::none(
+        &["_______", "_______", "_______", "_______", "_______", "_______"],
+        vec![]
+    )]
+    fn test_winning_moves(#[case] data: &[&str], #[case] expected: Vec<usize>) {
+        let board = Board::new_from_str_vec(data);
+
+        assert_eq!(board.winning_moves(Square::Yellow), expected);
+        assert_eq!(board.moves_that_block(Square::Red), expected);
+    }
+
+    #[test]
+    fn test_winning_moves_match_trying_every_move() {
+        let board = Board::from_moves("4453366").unwrap();
+
+        for color in [Square::Yellow, Square::Red] {
+            let expected: Vec<_> = board
+                .list_valid_moves()
+                .into_iter()
+                .filter(|column| {
+                    let mut child = board.clone();
+                    child.apply_move(*column, color).unwrap().is_some()
+                })
+                .collect();
+
+            assert_eq!(board.winning_moves(color), expected);
+        }
+    }
+
+    #[test]
+    fn test_threats() {
+        let board = Board::new_from_str_vec(&[
+            "_______", "_______", "_______", "___Y___", "__YR___", "RYRRY__",
+        ]);
+
+        assert_eq!(board.threats(Square::Yellow), vec![(3, 4)]);
+        assert_eq!(board.threats(Square::Red), vec![]);
+        assert_eq!(board.threats(Square::Empty), vec![]);
+    }
+
+    #[test]
+    fn test_threats_stay_in_their_column() {
+        // Three on top of the first column must not spill over into the bottom of the second.
+        let board = Board::new_from_str_vec(&[
+            "Y______", "Y______", "Y______", "R______", "R______", "R______",
+        ]);
+
+        assert_eq!(board.threats(Square::Yellow), vec![]);
+        assert_eq!(board.threats(Square::Red), vec![]);
+    }
+
+    #[test]
+    fn test_moves_that_lose_immediately() {
+        let board = Board::new_from_str_vec(&[
+            "_______", "_______", "_______", "___Y___", "__YRR__", "RYRRY_Y",
+        ]);
+
+        assert_eq!(board.moves_that_lose_immediately(Square::Red), vec![4]);
+        assert!(board.moves_that_lose_immediately(Square::Yellow).is_empty());
+    }
+
+    #[test]
+    fn test_winning_move_is_not_losing() {
+        // Yellow's winning drop in column 4 also lands under Red's threat.
+        let board = Board::new_from_str_vec(&[
+            "_______", "_______", "_______", "_RRR___", "RYYY___", "YRYYR__",
+        ]);
+
+        assert_eq!(board.winning_moves(Square::Yellow), vec![4]);
+        assert_eq!(board.moves_that_block(Square::Yellow), vec![0]);
+        assert!(board.moves_that_lose_immediately(Square::Yellow).is_empty());
+    }
+
+    #[test]
+    fn test_queries_on_finished_game() {
+        let board = Board::from_moves("1212121").unwrap();
+
+        assert!(board.winning_moves(Square::Red).is_empty());
+        assert!(board.moves_that_block(Square::Red).is_empty());
+        assert!(board.moves_that_lose_immediately(Square::Red).is_empty());
+    }
+
+    #[test]
+    fn test_connect_three_threats() {
+        let mut board = Board::with_dimensions(5, 4, 3).unwrap();
+        board.play_moves("1122").unwrap();
+
+        assert_eq!(board.winning_moves(Square::Yellow), vec![2]);
+        assert_eq!(board.winning_moves(Square::Red), vec![]);
+        assert_eq!(board.threats(Square::Yellow), vec![(0, 2)]);
+        assert_eq!(board.threats(Square::Red), vec![(1, 2)]);
+    }
+}
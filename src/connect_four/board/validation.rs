@@ -0,0 +1,199 @@
+use super::Board;
+use crate::connect_four::square::Square;
+
+/// Ways a `Board` can differ from every position reachable in a game.
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum IllegalPosition {
+    #[error("The disc in column {column}, row {row} has nothing under it.")]
+    FloatingDisc { row: usize, column: usize },
+    #[error("{yellow} Yellow and {red} Red discs can't come up in a game.")]
+    ImpossibleCounts { yellow: usize, red: usize },
+    #[error("Both Yellow and Red have a line.")]
+    BothWin,
+    #[error("{winner:?} had already won but play continued.")]
+    PlayedAfterGameOver { winner: Square },
+}
+
+impl Board {
+    /// Check that the position can come up in a game of connect four.
+    ///
+    /// Every disc has to rest on the bottom or on another disc, and Yellow, who moves first,
+    /// has the same number of discs as Red or one more. At most one colour can have a line, and
+    /// if one does the winning disc has to be the last one played: a single disc on top of a
+    /// column that was the winner's last move and completes every one of their lines.
+    pub fn validate(&self) -> Result<(), IllegalPosition> {
+        for column in 0..self.width {
+            let discs = self.mask & self.column_mask(column);
+            let bottom = self.bottom_mask(column);
+
+            // A column without gaps is a run of bits starting at the bottom, so adding the
+            // bottom bit clears all of them.
+            if discs & (discs + bottom) != 0 {
+                let gap = (discs + bottom) & !discs;
+                let floating = discs & !(gap - 1);
+                let (row, column) = self.coordinates(floating & floating.wrapping_neg());
+
+                return Err(IllegalPosition::FloatingDisc { row, column });
+            }
+        }
+
+        let yellow = self.color_mask(Square::Yellow);
+        let red = self.color_mask(Square::Red);
+        let yellow_count = yellow.count_ones() as usize;
+        let red_count = red.count_ones() as usize;
+
+        if yellow_count != red_count && yellow_count != red_count + 1 {
+            return Err(IllegalPosition::ImpossibleCounts {
+                yellow: yellow_count,
+                red: red_count,
+            });
+        }
+
+        let (winner, discs) = match (self.has_line(yellow), self.has_line(red)) {
+            (true, true) => return Err(IllegalPosition::BothWin),
+            (true, false) => (Square::Yellow, yellow),
+            (false, true) => (Square::Red, red),
+            (false, false) => return Ok(()),
+        };
+
+        // The winner has to have played the last disc...
+        let last_to_move = self.side_to_move().flip_into();
+
+        // ...and taking one of their discs off the top of a column has to undo every line.
+        let undoes_win = (0..self.width).any(|column| {
+            let column_discs = self.mask & self.column_mask(column);
+            if column_discs == 0 {
+                return false;
+            }
+
+            let top = 1 << (u64::BITS - 1 - column_discs.leading_zeros());
+            discs & top != 0 && !self.has_line(discs & !top)
+        });
+
+        if winner != last_to_move || !undoes_win {
+            return Err(IllegalPosition::PlayedAfterGameOver { winner });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::empty(&["_______", "_______", "_______", "_______", "_______", "_______"])]
+    #[case::one_disc(&["_______", "_______", "_______", "_______", "_______", "___Y___"])]
+    #[case::even(&["_______", "_______", "_______", "_______", "___R___", "__RYY__"])]
+    #[case::yellow_won(&[
+        "_______",
+        "_______",
+        "Y______",
+        "YR_____",
+        "YR_____",
+        "YR_____",
+    ])]
+    #[case::red_won(&[
+        "_______",
+        "_______",
+        "_______",
+        "_______",
+        "YY_____",
+        "RRRRYY_",
+    ])]
+    #[case::two_lines_at_once(&[
+        "_______",
+        "_______",
+        "YYYY___",
+        "YRRY___",
+        "YYRR___",
+        "YRRYRRR",
+    ])]
+    #[case::full(&[
+        "RYRYRYR",
+        "RYRYRYR",
+        "RYRYRYR",
+        "YRYRYRY",
+        "YRYRYRY",
+        "YRYRYRY",
+    ])]
+    fn test_validate(#[case] data: &[&str; 6]) {
+        let board = Board::new_from_str_vec(data);
+
+        assert_eq!(board.validate(), Ok(()));
+    }
+
+    #[rstest]
+    #[case::floating(&[
+        "_______",
+        "_______",
+        "_______",
+        "___Y___",
+        "_______",
+        "___R___",
+    ], IllegalPosition::FloatingDisc { row: 2, column: 3 })]
+    #[case::floating_on_empty_column(&[
+        "_______",
+        "_______",
+        "_______",
+        "_______",
+        "R______",
+        "_Y_____",
+    ], IllegalPosition::FloatingDisc { row: 1, column: 0 })]
+    #[case::too_many_yellow(&[
+        "_______",
+        "_______",
+        "_______",
+        "_______",
+        "_______",
+        "YY_____",
+    ], IllegalPosition::ImpossibleCounts { yellow: 2, red: 0 })]
+    #[case::too_many_red(&[
+        "_______",
+        "_______",
+        "_______",
+        "_______",
+        "_______",
+        "YRR____",
+    ], IllegalPosition::ImpossibleCounts { yellow: 1, red: 2 })]
+    #[case::both_win(&[
+        "_______",
+        "_______",
+        "YR_____",
+        "YR_____",
+        "YR_____",
+        "YR_____",
+    ], IllegalPosition::BothWin)]
+    #[case::loser_moved_after(&[
+        "_______",
+        "_______",
+        "Y______",
+        "YR_____",
+        "YR_____",
+        "YRR____",
+    ], IllegalPosition::PlayedAfterGameOver { winner: Square::Yellow })]
+    #[case::winner_moved_after(&[
+        "Y______",
+        "R______",
+        "Y______",
+        "YR_____",
+        "YR_____",
+        "YR_____",
+    ], IllegalPosition::PlayedAfterGameOver { winner: Square::Yellow })]
+    #[case::buried_line(&[
+        "_______",
+        "_______",
+        "_______",
+        "_______",
+        "RRRY___",
+        "YYYY_R_",
+    ], IllegalPosition::PlayedAfterGameOver { winner: Square::Yellow })]
+    fn test_validate_fail(#[case] data: &[&str; 6], #[case] expected: IllegalPosition) {
+        let board = Board::new_from_str_vec(data);
+
+        assert_eq!(board.validate(), Err(expected));
+    }
+}
@@ -0,0 +1,67 @@
+//! Zobrist keys for positions.
+//!
+//! Every (colour, cell) pair gets a fixed random number and a position's key is the XOR of the
+//! numbers for its discs, so playing or taking back a disc updates the key with a single XOR.
+//! The numbers come from a fixed seed and are built at compile time, so keys are the same on
+//! every run and can be stored on disk.
+
+use crate::connect_four::square::Square;
+
+/// Keys are indexed by bit position, and boards never use more than 64 bits.
+const CELLS: usize = u64::BITS as usize;
+
+const SEED: u64 = 0x0C0F_FEE0_C4F0_0D5E;
+
+const KEYS: [[u64; CELLS]; 2] = generate_keys();
+
+/// Fill the table with SplitMix64, which is simple enough to run in a `const fn`.
+const fn generate_keys() -> [[u64; CELLS]; 2] {
+    let mut keys = [[0; CELLS]; 2];
+    let mut state = SEED;
+
+    let mut color = 0;
+    while color < 2 {
+        let mut cell = 0;
+        while cell < CELLS {
+            state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            keys[color][cell] = z ^ (z >> 31);
+            cell += 1;
+        }
+        color += 1;
+    }
+
+    keys
+}
+
+/// The number for a `color` disc at bit `index` of the bitboards.
+pub(super) fn disc_key(color: Square, index: usize) -> u64 {
+    match color {
+        Square::Yellow => KEYS[0][index],
+        Square::Red => KEYS[1][index],
+        Square::Empty => 0,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_keys_are_distinct() {
+        let mut all: Vec<_> = KEYS.iter().flatten().collect();
+        all.sort();
+        all.dedup();
+
+        assert_eq!(all.len(), 2 * CELLS);
+    }
+
+    #[test]
+    fn test_disc_key() {
+        assert_eq!(disc_key(Square::Yellow, 3), KEYS[0][3]);
+        assert_eq!(disc_key(Square::Red, 3), KEYS[1][3]);
+        assert_eq!(disc_key(Square::Empty, 3), 0);
+    }
+}
@@ -1,4 +1,6 @@
-use crate::connect_four::board::GameResult;
+use std::fmt::{Display, Formatter};
+
+use crate::connect_four::board::{GameResult, Line};
 
 use super::{
     board::{self, Board},
@@ -16,18 +18,79 @@ pub enum Error {
 
 type ConnectFourPlayer = dyn Player<MoveData = usize>;
 
+/// What it takes to play a game again move for move: the starting position, the moves, and the
+/// seeds of the players that choose at random.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameRecord {
+    start: Board,
+    moves: String,
+    yellow_seed: Option<u64>,
+    red_seed: Option<u64>,
+}
+
+impl GameRecord {
+    pub fn start(&self) -> &Board {
+        &self.start
+    }
+
+    /// The columns played, as 1-based numbers that `Board::play_moves` replays onto the start.
+    pub fn moves(&self) -> &str {
+        &self.moves
+    }
+
+    pub fn yellow_seed(&self) -> Option<u64> {
+        self.yellow_seed
+    }
+
+    pub fn red_seed(&self) -> Option<u64> {
+        self.red_seed
+    }
+}
+
+impl Display for GameRecord {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Start: {}", self.start.to_notation())?;
+        write!(f, "Moves: {}", self.moves)?;
+
+        for (color, seed) in [
+            (Square::Yellow, self.yellow_seed),
+            (Square::Red, self.red_seed),
+        ] {
+            if let Some(seed) = seed {
+                write!(f, "\n{color:?} seed: {seed}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
 pub struct Game {
     color_to_be_played: Square,
+    start: Board,
     board: Board,
+    moves: Vec<usize>,
     yellow_player: Box<ConnectFourPlayer>,
     red_player: Box<ConnectFourPlayer>,
 }
 
 impl Game {
     pub fn new(yellow_player: Box<ConnectFourPlayer>, red_player: Box<ConnectFourPlayer>) -> Self {
+        Self::with_board(yellow_player, red_player, Board::new())
+    }
+
+    /// Start a game from `board`, e.g. one made with `Board::with_dimensions` or
+    /// `Board::from_moves`.
+    pub fn with_board(
+        yellow_player: Box<ConnectFourPlayer>,
+        red_player: Box<ConnectFourPlayer>,
+        board: Board,
+    ) -> Self {
         Self {
-            color_to_be_played: Square::Yellow,
-            board: Board::new(),
+            color_to_be_played: board.side_to_move(),
+            start: board.clone(),
+            board,
+            moves: vec![],
             yellow_player,
             red_player,
         }
@@ -49,15 +112,19 @@ impl Game {
 
             let player_move = self.get_player_move_loop()?;
 
-            self.play_move(player_move)?;
+            let result = self.play_move(player_move)?;
+
+            self.yellow_player.notify_move(&self.board, player_move);
+            self.red_player.notify_move(&self.board, player_move);
 
             self.color_to_be_played.flip();
 
-            if let Some(result) = self.board.check_for_win() {
+            if let Some(result) = result {
                 match result {
-                    GameResult::Win(color) => self.print_win(color),
+                    GameResult::Win(color, lines) => self.print_win(color, &lines),
                     GameResult::Draw => self.print_draw(),
                 }
+                println!("{}", self.record());
                 break;
             }
         }
@@ -106,10 +173,33 @@ impl Game {
         player.get_move(&self.board)
     }
 
-    fn play_move(&mut self, column: usize) -> Result<()> {
-        self.board.apply_move(column, self.color_to_be_played)?;
+    /// The columns played so far, as 1-based numbers that `Board::play_moves` can replay onto
+    /// the starting board. They are digits, or separated by commas on boards wider than nine.
+    pub fn move_sequence(&self) -> String {
+        let separator = if self.start.width() > 9 { "," } else { "" };
 
-        Ok(())
+        self.moves
+            .iter()
+            .map(|m| (m + 1).to_string())
+            .collect::<Vec<_>>()
+            .join(separator)
+    }
+
+    /// The game so far, with the seeds that replay it.
+    pub fn record(&self) -> GameRecord {
+        GameRecord {
+            start: self.start.clone(),
+            moves: self.move_sequence(),
+            yellow_seed: self.yellow_player.seed(),
+            red_seed: self.red_player.seed(),
+        }
+    }
+
+    fn play_move(&mut self, column: usize) -> Result<Option<GameResult>> {
+        let result = self.board.apply_move(column, self.color_to_be_played)?;
+        self.moves.push(column);
+
+        Ok(result)
     }
 
     fn get_current_player(&self) -> &ConnectFourPlayer {
@@ -131,12 +221,20 @@ impl Game {
         }
     }
 
-    fn print_win(&self, color: Square) {
+    fn print_win(&self, color: Square, lines: &[Line]) {
         println!();
         println!();
-        println!("{}", self.board);
+        println!("{:#}", self.board);
         println!();
         println!("{color:?} has won!!");
+
+        for line in lines {
+            let cells: Vec<_> = line
+                .iter()
+                .map(|(row, column)| format!("({column}, {row})"))
+                .collect();
+            println!("Line: {}", cells.join(" "));
+        }
     }
 
     fn print_draw(&self) {
@@ -147,3 +245,129 @@ impl Game {
         println!("Draw!");
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::{cell::RefCell, collections::VecDeque, rc::Rc};
+
+    use crate::connect_four::player::Random;
+
+    /// Plays a fixed list of columns, and keeps the moves it is told about.
+    struct Scripted(VecDeque<usize>, Rc<RefCell<Vec<(usize, usize)>>>);
+
+    impl Player for Scripted {
+        type MoveData = usize;
+
+        fn is_human(&self) -> bool {
+            false
+        }
+
+        fn get_move(&mut self, _current_board: &Board) -> Result<Self::MoveData> {
+            Ok(self.0.pop_front().unwrap())
+        }
+
+        fn notify_move(&mut self, board: &Board, column: usize) {
+            self.1.borrow_mut().push((board.moves_played(), column));
+        }
+    }
+
+    fn scripted(moves: &[usize]) -> Box<Scripted> {
+        Box::new(Scripted(moves.iter().copied().collect(), Rc::default()))
+    }
+
+    #[test]
+    fn test_move_sequence() {
+        let mut game = Game::new(scripted(&[0, 0, 0, 0]), scripted(&[1, 1, 1]));
+
+        game.game_loop().unwrap();
+
+        assert_eq!(game.move_sequence(), "1212121");
+        assert_eq!(
+            Board::from_moves(&game.move_sequence()).unwrap(),
+            game.board
+        );
+    }
+
+    #[test]
+    fn test_move_sequence_on_wide_board() {
+        let board = Board::with_dimensions(10, 5, 4).unwrap();
+        let mut game = Game::with_board(scripted(&[9, 9, 9, 9]), scripted(&[0, 0, 0]), board);
+
+        game.game_loop().unwrap();
+
+        assert_eq!(game.move_sequence(), "10,1,10,1,10,1,10");
+
+        let mut replayed = game.start.clone();
+        replayed.play_moves(&game.move_sequence()).unwrap();
+        assert_eq!(replayed, game.board);
+    }
+
+    #[test]
+    fn test_with_board_continues_position() {
+        let board = Board::from_moves("12121").unwrap();
+        let mut game = Game::with_board(scripted(&[0]), scripted(&[1]), board);
+
+        game.game_loop().unwrap();
+
+        // Red is to move in the starting position.
+        assert_eq!(game.move_sequence(), "21");
+        assert_eq!(Board::from_moves("1212121").unwrap(), game.board);
+    }
+
+    #[test]
+    fn test_players_are_notified_of_moves() {
+        let yellow = scripted(&[0, 0, 0, 0]);
+        let red = scripted(&[1, 1, 1]);
+        let (yellow_seen, red_seen) = (yellow.1.clone(), red.1.clone());
+
+        Game::new(yellow, red).game_loop().unwrap();
+
+        // Both hear about every move, the winning one included, after it is on the board.
+        let expected: Vec<_> = (1..=7).map(|played| (played, (played + 1) % 2)).collect();
+        assert_eq!(*yellow_seen.borrow(), expected);
+        assert_eq!(*red_seen.borrow(), expected);
+    }
+
+    #[test]
+    fn test_record_replays_seeded_game() {
+        let play = |yellow_seed, red_seed| {
+            let mut game = Game::new(
+                Box::new(Random::with_seed(yellow_seed)),
+                Box::new(Random::with_seed(red_seed)),
+            );
+            game.game_loop().unwrap();
+            game.record()
+        };
+
+        let record = play(3, 4);
+
+        assert_eq!(record.yellow_seed(), Some(3));
+        assert_eq!(record.red_seed(), Some(4));
+        assert_eq!(play(3, 4), record);
+
+        let mut replayed = record.start().clone();
+        replayed.play_moves(record.moves()).unwrap();
+        assert!(replayed.check_for_win().is_some());
+    }
+
+    #[test]
+    fn test_record_display() {
+        let board = Board::from_moves("12121").unwrap();
+        let mut game = Game::with_board(scripted(&[0]), Box::new(Random::with_seed(9)), board);
+
+        game.game_loop().unwrap();
+        let record = game.record();
+
+        assert_eq!(record.yellow_seed(), None);
+        assert_eq!(
+            record.to_string(),
+            format!(
+                "Start: {}\nMoves: {}\nRed seed: 9",
+                Board::from_moves("12121").unwrap().to_notation(),
+                record.moves()
+            )
+        );
+    }
+}
@@ -1,4 +1,4 @@
-mod board;
+pub mod board;
 pub mod game;
 pub mod player;
 pub mod square;
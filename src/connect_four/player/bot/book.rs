@@ -0,0 +1,413 @@
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+};
+
+use crate::connect_four::board::{Board, GameResult};
+
+use super::{
+    solver::{Score, SolveError, Solver},
+    transposition::TranspositionTable,
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum BookError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("Not an opening book.")]
+    BadMagic,
+    #[error(
+        "Opening book version {found} is not supported, expected {}.",
+        OpeningBook::VERSION
+    )]
+    UnsupportedVersion { found: u16 },
+    #[error("The opening book was built with different position keys.")]
+    KeyMismatch,
+    #[error("The opening book is truncated.")]
+    Truncated,
+    #[error("The opening book's checksum doesn't match its contents.")]
+    ChecksumMismatch,
+    #[error(transparent)]
+    Solve(#[from] SolveError),
+}
+
+/// Solved positions from the start of the game, so the bot can play the opening instantly.
+///
+/// A position and its mirror image share one entry, under the board's canonical key, with the
+/// best move stored for the canonical side.
+///
+/// On disk a book is a header, the entries sorted by key and a checksum:
+///
+/// | Bytes | Contents |
+/// | --- | --- |
+/// | 4 | `C4OB` |
+/// | 2 | Format version |
+/// | 4 | Width, height, connect n and the last ply in the book |
+/// | 8 | Key of the board after a disc in column 4, to spot books made with other keys |
+/// | 4 | Number of entries |
+/// | 10 each | Key, value and best move of every entry |
+/// | 8 | FNV-1a hash of everything before it |
+///
+/// Numbers are little endian.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpeningBook {
+    width: usize,
+    height: usize,
+    connect_n: usize,
+    max_ply: usize,
+    entries: HashMap<u64, (i8, u8)>,
+}
+
+impl OpeningBook {
+    const MAGIC: &'static [u8; 4] = b"C4OB";
+    const VERSION: u16 = 1;
+    const HEADER_LEN: usize = 4 + 2 + 4 + 8 + 4;
+    const ENTRY_LEN: usize = 8 + 1 + 1;
+
+    /// Solve `root` and every position up to `max_ply` discs that can follow it. `progress` is
+    /// told how many positions are done out of how many there are after each one.
+    ///
+    /// Only the positions with `max_ply` discs go to the solver. They are scored first, and
+    /// every other position is then scored from the positions its moves lead to, which costs
+    /// next to nothing. The time it takes is about that of solving the deepest positions.
+    pub fn generate(
+        root: &Board,
+        max_ply: usize,
+        solver: &mut Solver,
+        mut progress: impl FnMut(usize, usize),
+    ) -> Result<Self, BookError> {
+        Solver::check_dimensions(root)?;
+
+        let mut positions = vec![];
+        let mut seen = HashSet::new();
+        Self::collect(root, max_ply, &mut seen, &mut positions);
+
+        // Deepest first, so a position's moves all lead to positions already in the book.
+        positions.sort_by_key(|board| Reverse(board.moves_played()));
+
+        let mut book = Self {
+            width: root.width(),
+            height: root.height(),
+            connect_n: root.connect_n(),
+            max_ply,
+            entries: HashMap::new(),
+        };
+
+        for (done, board) in positions.iter().enumerate() {
+            let score = match board.moves_played() {
+                ply if ply == max_ply => solver.solve(board)?,
+                _ => book.score_from_moves(board),
+            };
+            book.insert(board, &score);
+            progress(done + 1, positions.len());
+        }
+
+        Ok(book)
+    }
+
+    /// Every unfinished position from `board` on, one of each mirror image pair.
+    fn collect(board: &Board, max_ply: usize, seen: &mut HashSet<u64>, positions: &mut Vec<Board>) {
+        if board.moves_played() > max_ply
+            || board.check_for_win().is_some()
+            || !seen.insert(board.canonical_key())
+        {
+            return;
+        }
+
+        positions.push(board.clone());
+
+        for column in board.list_valid_moves() {
+            let mut child = board.clone();
+            // SAFETY: The column comes from the valid moves.
+            child.apply_move(column, board.side_to_move()).unwrap();
+            Self::collect(&child, max_ply, seen, positions);
+        }
+    }
+
+    /// The score of `board` from the book's scores for the positions after each move. Of
+    /// equally good moves, the one nearest the centre is best, as with the solver.
+    fn score_from_moves(&self, board: &Board) -> Score {
+        let moves = board.moves_played();
+        let mut columns = board.list_valid_moves();
+        columns.sort_by_key(|column| column.abs_diff(board.width() / 2));
+
+        let mut best: Option<Score> = None;
+
+        for column in columns {
+            let mut child = board.clone();
+
+            // SAFETY: The column comes from the valid moves.
+            let score = match child.apply_move(column, board.side_to_move()).unwrap() {
+                Some(GameResult::Win(..)) => return Score::win_next(moves, column),
+                Some(GameResult::Draw) => Score::new(0, moves, column),
+                // SAFETY: Positions are scored deepest first, so the child is in the book.
+                None => Score::new(-self.lookup(&child).unwrap().value(), moves, column),
+            };
+
+            if best.is_none_or(|best| score.value() > best.value()) {
+                best = Some(score);
+            }
+        }
+
+        // SAFETY: Only unfinished positions are scored, so there is at least one move.
+        best.unwrap()
+    }
+
+    fn insert(&mut self, board: &Board, score: &Score) {
+        let best_move = TranspositionTable::orient(board, score.best_move());
+
+        self.entries.insert(
+            board.canonical_key(),
+            (score.value() as i8, best_move as u8),
+        );
+    }
+
+    /// The score of `board`, if it is in the book.
+    pub fn lookup(&self, board: &Board) -> Option<Score> {
+        let dimensions = (board.width(), board.height(), board.connect_n());
+        if dimensions != (self.width, self.height, self.connect_n)
+            || board.moves_played() > self.max_ply
+        {
+            return None;
+        }
+
+        let (value, best_move) = self.entries.get(&board.canonical_key())?;
+        let best_move = TranspositionTable::orient(board, *best_move as usize);
+
+        Some(Score::new(*value as i32, board.moves_played(), best_move))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The most discs a position in the book has.
+    pub fn max_ply(&self) -> usize {
+        self.max_ply
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes =
+            Vec::with_capacity(Self::HEADER_LEN + self.entries.len() * Self::ENTRY_LEN + 8);
+
+        bytes.extend_from_slice(Self::MAGIC);
+        bytes.extend_from_slice(&Self::VERSION.to_le_bytes());
+        for dimension in [self.width, self.height, self.connect_n, self.max_ply] {
+            bytes.push(dimension as u8);
+        }
+        bytes.extend_from_slice(&Self::key_check().to_le_bytes());
+        bytes.extend_from_slice(&(self.entries.len() as u32).to_le_bytes());
+
+        // Sorted, so the same book always makes the same file.
+        let mut entries: Vec<_> = self.entries.iter().collect();
+        entries.sort_by_key(|(key, _)| **key);
+
+        for (key, (value, best_move)) in entries {
+            bytes.extend_from_slice(&key.to_le_bytes());
+            bytes.push(*value as u8);
+            bytes.push(*best_move);
+        }
+
+        let checksum = Self::checksum(&bytes);
+        bytes.extend_from_slice(&checksum.to_le_bytes());
+
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BookError> {
+        if bytes.len() < Self::MAGIC.len() || &bytes[..Self::MAGIC.len()] != Self::MAGIC {
+            return Err(BookError::BadMagic);
+        }
+        if bytes.len() < Self::HEADER_LEN + 8 {
+            return Err(BookError::Truncated);
+        }
+
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version != Self::VERSION {
+            return Err(BookError::UnsupportedVersion { found: version });
+        }
+
+        let (contents, checksum) = bytes.split_at(bytes.len() - 8);
+        let count = u32::from_le_bytes(contents[18..22].try_into().unwrap()) as usize;
+        if contents.len() != Self::HEADER_LEN + count * Self::ENTRY_LEN {
+            return Err(BookError::Truncated);
+        }
+        if u64::from_le_bytes(checksum.try_into().unwrap()) != Self::checksum(contents) {
+            return Err(BookError::ChecksumMismatch);
+        }
+        if u64::from_le_bytes(contents[10..18].try_into().unwrap()) != Self::key_check() {
+            return Err(BookError::KeyMismatch);
+        }
+
+        let entries = contents[Self::HEADER_LEN..]
+            .chunks_exact(Self::ENTRY_LEN)
+            .map(|entry| {
+                let key = u64::from_le_bytes(entry[..8].try_into().unwrap());
+                (key, (entry[8] as i8, entry[9]))
+            })
+            .collect();
+
+        Ok(Self {
+            width: bytes[6] as usize,
+            height: bytes[7] as usize,
+            connect_n: bytes[8] as usize,
+            max_ply: bytes[9] as usize,
+            entries,
+        })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, BookError> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), BookError> {
+        Ok(fs::write(path, self.to_bytes())?)
+    }
+
+    /// Changes whenever the Zobrist keys do, which would make every stored key stale.
+    fn key_check() -> u64 {
+        // SAFETY: The move is valid on the empty board.
+        Board::from_moves("4").unwrap().key()
+    }
+
+    fn checksum(bytes: &[u8]) -> u64 {
+        bytes.iter().fold(0xCBF2_9CE4_8422_2325, |hash, byte| {
+            (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01B3)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ROOT: &str = "2246237261472515267444";
+
+    fn book() -> OpeningBook {
+        let root = Board::from_moves(ROOT).unwrap();
+
+        OpeningBook::generate(
+            &root,
+            root.moves_played() + 2,
+            &mut Solver::new(),
+            |_, _| {},
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_generate_and_lookup() {
+        let book = book();
+        let root = Board::from_moves(ROOT).unwrap();
+        let mut solver = Solver::new();
+
+        assert!(book.len() > 1);
+        assert_eq!(book.lookup(&root), Some(solver.solve(&root).unwrap()));
+
+        for column in root.list_valid_moves() {
+            let mut child = root.clone();
+            child.apply_move(column, root.side_to_move()).unwrap();
+
+            let expected = match child.check_for_win() {
+                Some(_) => None,
+                None => Some(solver.solve(&child).unwrap().value()),
+            };
+            assert_eq!(book.lookup(&child).map(|score| score.value()), expected);
+        }
+    }
+
+    #[test]
+    fn test_lookup_mirrored() {
+        let book = book();
+        let root = Board::from_moves(ROOT).unwrap();
+        let score = book.lookup(&root).unwrap();
+
+        let mirrored = book.lookup(&root.mirrored()).unwrap();
+
+        assert_eq!(mirrored.value(), score.value());
+        assert_eq!(mirrored.best_move(), root.mirror_column(score.best_move()));
+    }
+
+    #[test]
+    fn test_lookup_outside_book() {
+        let book = book();
+
+        assert_eq!(book.lookup(&Board::new()), None);
+        assert_eq!(book.lookup(&Board::with_dimensions(6, 5, 4).unwrap()), None);
+
+        let mut deeper = Board::from_moves(ROOT).unwrap();
+        deeper.play_moves("333").unwrap();
+        assert_eq!(book.lookup(&deeper), None);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let book = book();
+
+        let bytes = book.to_bytes();
+
+        assert_eq!(bytes.len(), 22 + 10 * book.len() + 8);
+        assert_eq!(OpeningBook::from_bytes(&bytes).unwrap(), book);
+        assert_eq!(book.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_stale_books_are_rejected() {
+        let bytes = book().to_bytes();
+
+        let mut corrupted = bytes.clone();
+        corrupted[30] ^= 1;
+        assert!(matches!(
+            OpeningBook::from_bytes(&corrupted),
+            Err(BookError::ChecksumMismatch)
+        ));
+
+        let mut old_version = bytes.clone();
+        old_version[4] = 0;
+        assert!(matches!(
+            OpeningBook::from_bytes(&old_version),
+            Err(BookError::UnsupportedVersion { found: 0 })
+        ));
+
+        assert!(matches!(
+            OpeningBook::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(BookError::Truncated)
+        ));
+        assert!(matches!(
+            OpeningBook::from_bytes(b"not a book"),
+            Err(BookError::BadMagic)
+        ));
+    }
+
+    #[test]
+    fn test_other_keys_are_rejected() {
+        let mut bytes = book().to_bytes();
+        bytes[10] ^= 1;
+
+        // Fix up the checksum so only the key check is wrong.
+        let contents = bytes.len() - 8;
+        let checksum = OpeningBook::checksum(&bytes[..contents]);
+        bytes[contents..].copy_from_slice(&checksum.to_le_bytes());
+
+        assert!(matches!(
+            OpeningBook::from_bytes(&bytes),
+            Err(BookError::KeyMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_generate_rejects_other_dimensions() {
+        let board = Board::with_dimensions(6, 5, 4).unwrap();
+
+        assert!(matches!(
+            OpeningBook::generate(&board, 0, &mut Solver::new(), |_, _| {}),
+            Err(BookError::Solve(SolveError::UnsupportedDimensions))
+        ));
+    }
+}
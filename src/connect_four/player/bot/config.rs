@@ -0,0 +1,262 @@
+use std::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+    time::Duration,
+};
+
+#[derive(thiserror::Error, Debug)]
+#[error("Unknown difficulty \"{0}\". Expected one of easy, medium, hard or perfect.")]
+pub struct ParseDifficultyError(String);
+
+/// Named presets for how strongly the bot plays.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum Difficulty {
+    /// Shallow search that regularly throws away its best move.
+    Easy,
+    #[default]
+    Medium,
+    Hard,
+    /// Searches all the way to the end of the game, or until its node budget runs out, which
+    /// takes a few seconds on one core. Without the budget it can take hours in the opening.
+    Perfect,
+}
+
+impl FromStr for Difficulty {
+    type Err = ParseDifficultyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "easy" => Ok(Self::Easy),
+            "medium" => Ok(Self::Medium),
+            "hard" => Ok(Self::Hard),
+            "perfect" => Ok(Self::Perfect),
+            _ => Err(ParseDifficultyError(s.to_string())),
+        }
+    }
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let name = match self {
+            Self::Easy => "easy",
+            Self::Medium => "medium",
+            Self::Hard => "hard",
+            Self::Perfect => "perfect",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+/// How far and how long the bot searches before picking a move.
+///
+/// Start from a [`Difficulty`] preset and override individual settings with the `with_*` methods.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BotConfig {
+    difficulty: Difficulty,
+    depth: usize,
+    node_budget: Option<u64>,
+    move_time: Option<Duration>,
+    blunder_chance: f64,
+    table_capacity: usize,
+    move_ordering: bool,
+    threads: usize,
+    pondering: bool,
+}
+
+impl BotConfig {
+    const DEFAULT_TABLE_CAPACITY: usize = 1 << 18;
+    const PERFECT_NODE_BUDGET: u64 = 5_000_000;
+
+    pub fn new(difficulty: Difficulty) -> Self {
+        let (depth, blunder_chance) = match difficulty {
+            Difficulty::Easy => (2, 0.3),
+            Difficulty::Medium => (4, 0.0),
+            Difficulty::Hard => (6, 0.0),
+            // Enough plies to fill any board.
+            Difficulty::Perfect => (usize::MAX, 0.0),
+        };
+
+        // The end of the game is too far away to reach from the opening.
+        let node_budget = (difficulty == Difficulty::Perfect).then_some(Self::PERFECT_NODE_BUDGET);
+
+        Self {
+            difficulty,
+            depth,
+            node_budget,
+            move_time: None,
+            blunder_chance,
+            table_capacity: Self::DEFAULT_TABLE_CAPACITY,
+            move_ordering: true,
+            threads: 1,
+            pondering: false,
+        }
+    }
+
+    /// Maximum number of plies searched.
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// Stop searching once this many nodes have been visited, and play the best move of the
+    /// last depth searched to the end. With more than one thread, only the nodes of the first
+    /// count.
+    pub fn with_node_budget(mut self, nodes: u64) -> Self {
+        self.node_budget = Some(nodes);
+        self
+    }
+
+    /// Stop searching once this much time has passed, and play the best move of the last depth
+    /// searched to the end. The bot answers a little after the time is up, never before.
+    pub fn with_move_time(mut self, move_time: Duration) -> Self {
+        self.move_time = Some(move_time);
+        self
+    }
+
+    /// Probability, clamped to `0.0..=1.0`, of playing a random other move instead of the best.
+    pub fn with_blunder_chance(mut self, chance: f64) -> Self {
+        self.blunder_chance = chance.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Number of positions the transposition table holds. This caps the bot's memory use no
+    /// matter how long it searches.
+    pub fn with_table_capacity(mut self, capacity: usize) -> Self {
+        self.table_capacity = capacity;
+        self
+    }
+
+    /// Try the most promising moves first. Without it moves are searched left to right, which
+    /// gives the same result more slowly, so this is only worth turning off to measure it.
+    pub fn with_move_ordering(mut self, move_ordering: bool) -> Self {
+        self.move_ordering = move_ordering;
+        self
+    }
+
+    /// Search on this many threads, at least one. The extra threads search the same position
+    /// and share what they find through the table, which lets the bot search deeper in the same
+    /// time. Only a single thread, the default, is sure to pick the same move every time, as
+    /// the threads fill the table in a different order each search.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Keep searching in the background while the opponent thinks, on the position after the
+    /// reply the bot expects. This needs the game to tell the bot about every move played.
+    pub fn with_pondering(mut self, pondering: bool) -> Self {
+        self.pondering = pondering;
+        self
+    }
+
+    pub fn difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn node_budget(&self) -> Option<u64> {
+        self.node_budget
+    }
+
+    pub fn move_time(&self) -> Option<Duration> {
+        self.move_time
+    }
+
+    pub fn blunder_chance(&self) -> f64 {
+        self.blunder_chance
+    }
+
+    pub fn table_capacity(&self) -> usize {
+        self.table_capacity
+    }
+
+    pub fn move_ordering(&self) -> bool {
+        self.move_ordering
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    pub fn pondering(&self) -> bool {
+        self.pondering
+    }
+}
+
+impl Default for BotConfig {
+    fn default() -> Self {
+        Self::new(Difficulty::default())
+    }
+}
+
+impl From<Difficulty> for BotConfig {
+    fn from(difficulty: Difficulty) -> Self {
+        Self::new(difficulty)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::easy("easy", Difficulty::Easy)]
+    #[case::medium("Medium", Difficulty::Medium)]
+    #[case::hard("HARD", Difficulty::Hard)]
+    #[case::perfect("perfect", Difficulty::Perfect)]
+    fn test_difficulty_from_str(#[case] given: &str, #[case] expected: Difficulty) {
+        assert_eq!(given.parse::<Difficulty>().unwrap(), expected);
+        assert_eq!(
+            expected.to_string().parse::<Difficulty>().unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_difficulty_from_str_fail() {
+        assert!("impossible".parse::<Difficulty>().is_err());
+    }
+
+    #[test]
+    fn test_harder_searches_deeper() {
+        let depths: Vec<_> = [
+            Difficulty::Easy,
+            Difficulty::Medium,
+            Difficulty::Hard,
+            Difficulty::Perfect,
+        ]
+        .into_iter()
+        .map(|d| BotConfig::new(d).depth())
+        .collect();
+
+        assert!(depths.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_only_perfect_has_node_budget() {
+        assert_eq!(BotConfig::new(Difficulty::Hard).node_budget(), None);
+        assert!(BotConfig::new(Difficulty::Perfect).node_budget().is_some());
+    }
+
+    #[test]
+    fn test_overrides() {
+        let config = BotConfig::new(Difficulty::Easy)
+            .with_depth(3)
+            .with_node_budget(1000)
+            .with_blunder_chance(2.0)
+            .with_threads(0);
+
+        assert_eq!(config.difficulty(), Difficulty::Easy);
+        assert_eq!(config.depth(), 3);
+        assert_eq!(config.node_budget(), Some(1000));
+        assert_eq!(config.move_time(), None);
+        assert_eq!(config.blunder_chance(), 1.0);
+        assert_eq!(config.threads(), 1);
+    }
+}
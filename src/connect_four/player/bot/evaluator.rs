@@ -0,0 +1,129 @@
+use crate::connect_four::{board::Board, square::Square};
+
+/// Scores positions the search stops at.
+///
+/// Finished games are scored by the search itself, so an evaluator only sees games that are
+/// still going. Scores must stay well below a million either way, which the search keeps for
+/// wins. The search may call it from several threads at once.
+pub trait Evaluator: Send + Sync {
+    /// Score of `board` for `color`, positive when it favours `color`.
+    fn evaluate(&self, board: &Board, color: Square) -> isize;
+}
+
+/// Any `Fn(&Board, Square) -> isize` works as an evaluator, which is handy for experiments.
+impl<F> Evaluator for F
+where
+    F: Fn(&Board, Square) -> isize + Send + Sync,
+{
+    fn evaluate(&self, board: &Board, color: Square) -> isize {
+        self(board, color)
+    }
+}
+
+/// `Board::eval`: threats, their rows, open windows and the centre. This is what the bot uses
+/// unless told otherwise.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct ThreatEvaluator;
+
+impl Evaluator for ThreatEvaluator {
+    fn evaluate(&self, board: &Board, color: Square) -> isize {
+        match color {
+            Square::Red => -board.eval(),
+            _ => board.eval(),
+        }
+    }
+}
+
+/// Sums a weight for every window of `connect_n` cells only one colour has discs in, by how
+/// many discs it holds. The opponent's windows count against.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowEvaluator {
+    weights: Vec<isize>,
+}
+
+impl WindowEvaluator {
+    /// `weights[k]` is the score of a window holding `k` discs. Fuller windows than there are
+    /// weights use the last one.
+    pub fn new(weights: Vec<isize>) -> Self {
+        Self { weights }
+    }
+
+    pub fn weights(&self) -> &[isize] {
+        &self.weights
+    }
+
+    fn score(&self, board: &Board, color: Square) -> isize {
+        board
+            .open_windows(color)
+            .iter()
+            .enumerate()
+            .map(|(discs, windows)| {
+                let weight = self
+                    .weights
+                    .get(discs)
+                    .or(self.weights.last())
+                    .copied()
+                    .unwrap_or(0);
+
+                weight * *windows as isize
+            })
+            .sum()
+    }
+}
+
+impl Default for WindowEvaluator {
+    fn default() -> Self {
+        Self::new(vec![0, 1, 4, 16])
+    }
+}
+
+impl Evaluator for WindowEvaluator {
+    fn evaluate(&self, board: &Board, color: Square) -> isize {
+        self.score(board, color) - self.score(board, color.flip_into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::threat(ThreatEvaluator)]
+    #[case::window(WindowEvaluator::default())]
+    fn test_perspective(#[case] evaluator: impl Evaluator) {
+        let board = Board::from_moves("414").unwrap();
+
+        let yellow = evaluator.evaluate(&board, Square::Yellow);
+        let red = evaluator.evaluate(&board, Square::Red);
+
+        assert!(yellow > 0);
+        assert_eq!(yellow, -red);
+    }
+
+    #[test]
+    fn test_window_weights() {
+        let board = Board::new_from_str_vec(&[
+            "_______", "_______", "_______", "_______", "_______", "YYYR___",
+        ]);
+        let evaluator = WindowEvaluator::new(vec![0, 1]);
+
+        // Six windows with one Yellow disc against four with the Red disc.
+        assert_eq!(evaluator.evaluate(&board, Square::Yellow), 2);
+        assert_eq!(
+            WindowEvaluator::new(vec![]).evaluate(&board, Square::Yellow),
+            0
+        );
+    }
+
+    #[test]
+    fn test_closure() {
+        let evaluator = |board: &Board, _: Square| board.moves_played() as isize;
+
+        assert_eq!(
+            evaluator.evaluate(&Board::from_moves("44").unwrap(), Square::Red),
+            2
+        );
+    }
+}
@@ -0,0 +1,1488 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+    time::Instant,
+};
+
+use crate::connect_four::{
+    board::{Board, GameResult},
+    player::Player,
+    square::Square,
+};
+
+use anyhow::Result;
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+mod book;
+mod config;
+mod evaluator;
+mod ordering;
+mod ponder;
+mod report;
+mod solver;
+mod stop;
+mod transposition;
+
+pub use book::{BookError, OpeningBook};
+pub use config::{BotConfig, Difficulty, ParseDifficultyError};
+pub use evaluator::{Evaluator, ThreatEvaluator, WindowEvaluator};
+pub use report::{
+    ColumnScore, HumanReadable, JsonLines, ReportSink, ScoreBound, SearchReport, Silent,
+};
+pub use solver::{Outcome, Score, SolveError, Solver};
+pub use stop::StopToken;
+
+use ordering::MoveOrdering;
+use ponder::Ponder;
+use transposition::{Bound, Entry, TranspositionTable};
+
+pub struct Bot {
+    color: Square,
+    config: BotConfig,
+    evaluator: Arc<dyn Evaluator>,
+    table: Arc<TranspositionTable>,
+    book: Option<OpeningBook>,
+    sink: Box<dyn ReportSink>,
+    last_report: Option<SearchReport>,
+    nodes_searched: u64,
+    ponder: Option<Ponder>,
+    stop: StopToken,
+    seed: u64,
+    rng: ChaCha8Rng,
+}
+
+impl Player for Bot {
+    type MoveData = usize;
+
+    fn is_human(&self) -> bool {
+        false
+    }
+
+    /// Searches until the bot's stop token is used, if that comes first. Only the search running
+    /// at the time stops: the token is reset as each move is searched.
+    fn get_move(&mut self, current_board: &Board) -> Result<Self::MoveData> {
+        let stop = self.stop.clone();
+        stop.reset();
+
+        self.get_move_with_stop(current_board, &stop)
+    }
+
+    fn seed(&self) -> Option<u64> {
+        Some(self.seed)
+    }
+
+    /// Start pondering after the bot's own moves, and stop as soon as the opponent has moved.
+    fn notify_move(&mut self, board: &Board, column: usize) {
+        self.stop_pondering();
+
+        if board.side_to_move() == self.color
+            || !self.config.pondering()
+            || board.check_for_win().is_some()
+        {
+            return;
+        }
+
+        let mut expected = board.clone();
+        if let Some(reply) = self.expected_reply(column) {
+            // SAFETY: The reply comes from the last search of this position.
+            let result = expected.apply_move(reply, expected.side_to_move()).unwrap();
+
+            // The game would be over, with nothing left to search.
+            if result.is_some() {
+                return;
+            }
+        }
+
+        // Without a reply to expect, searching the opponent's position still warms the table
+        // for every reply.
+        self.ponder = Some(Ponder::start(
+            &self.config,
+            Arc::clone(&self.evaluator),
+            Arc::clone(&self.table),
+            expected,
+        ));
+    }
+}
+
+impl Drop for Bot {
+    fn drop(&mut self) {
+        self.stop_pondering();
+    }
+}
+
+impl Bot {
+    pub fn new(color: Square) -> Self {
+        Self::with_config(color, BotConfig::default())
+    }
+
+    pub fn with_config(color: Square, config: BotConfig) -> Self {
+        let table = TranspositionTable::new(config.table_capacity());
+        let seed = rand::random();
+
+        Self {
+            color,
+            config,
+            evaluator: Arc::new(ThreatEvaluator),
+            table: Arc::new(table),
+            book: None,
+            sink: Box::new(Silent),
+            last_report: None,
+            nodes_searched: 0,
+            ponder: None,
+            stop: StopToken::new(),
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+
+    /// Score the positions where the search stops with `evaluator` instead of `Board::eval`.
+    pub fn with_evaluator(mut self, evaluator: impl Evaluator + 'static) -> Self {
+        self.evaluator = Arc::new(evaluator);
+        self
+    }
+
+    /// Play straight from `book` in the positions it has, and search everywhere else.
+    pub fn with_book(mut self, book: OpeningBook) -> Self {
+        self.book = Some(book);
+        self
+    }
+
+    /// Send a report to `sink` after every search. Moves from the book or played at random
+    /// aren't searched, so they aren't reported.
+    pub fn with_report_sink(mut self, sink: impl ReportSink + 'static) -> Self {
+        self.sink = Box::new(sink);
+        self
+    }
+
+    /// Make the bot's random choices, its first move and its blunders, from `seed`. Otherwise
+    /// it picks a seed of its own. Moves can still differ with more than one thread, pondering or
+    /// a move time, since they depend on timing.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self.rng = ChaCha8Rng::seed_from_u64(seed);
+        self
+    }
+
+    /// Stop each search when `stop` is used, e.g. from another thread or a signal handler.
+    pub fn with_stop_token(mut self, stop: StopToken) -> Self {
+        self.stop = stop;
+        self
+    }
+
+    /// A handle on the token that stops the bot's searches.
+    pub fn stop_token(&self) -> StopToken {
+        self.stop.clone()
+    }
+
+    pub fn config(&self) -> &BotConfig {
+        &self.config
+    }
+
+    /// Positions visited by the last search, on every thread.
+    pub fn nodes_searched(&self) -> u64 {
+        self.nodes_searched
+    }
+
+    /// The report of the last search, unless the last move wasn't searched.
+    pub fn last_report(&self) -> Option<&SearchReport> {
+        self.last_report.as_ref()
+    }
+
+    /// `get_move`, stopping once `stop` is used and playing the best move found by then. The
+    /// first ply is always searched, so there is a move to play even if it was used before the
+    /// search started. The token is left as it is.
+    pub fn get_move_with_stop(&mut self, current_board: &Board, stop: &StopToken) -> Result<usize> {
+        self.stop_pondering();
+
+        if let Some(score) = self
+            .book
+            .as_ref()
+            .and_then(|book| book.lookup(current_board))
+        {
+            self.nodes_searched = 0;
+            self.last_report = None;
+            return Ok(self.maybe_blunder(current_board, score.best_move()));
+        }
+
+        if current_board.is_empty() {
+            self.last_report = None;
+            return Ok(self.get_random_move(current_board));
+        }
+
+        let started = Instant::now();
+        let opponent_color = self.color.flip_into();
+        let mut tree = GameTree::new(
+            &self.config,
+            self.evaluator.as_ref(),
+            &self.table,
+            current_board,
+            opponent_color,
+        );
+        tree.budget = Arc::new(Budget::new(&self.config).with_stop(stop.clone()));
+        tree.search();
+        self.nodes_searched = tree.budget.nodes();
+
+        // SAFETY: A user won't be asked to pick a move if there is a draw.
+        let best_move = tree.best_move().unwrap();
+
+        // Reports are only diagnostics, so a sink that can't take one, e.g. because its pipe was
+        // closed, doesn't stop the move being played.
+        let report = tree.report(started.elapsed());
+        let _ = self.sink.report(&report);
+        self.last_report = Some(report);
+
+        Ok(self.maybe_blunder(current_board, best_move))
+    }
+
+    /// The position the bot is searching in the background, if it is pondering.
+    pub fn pondering(&self) -> Option<&Board> {
+        self.ponder.as_ref().map(|ponder| ponder.board())
+    }
+
+    /// The opponent's best reply to `column` in the last search, if the bot played the move
+    /// that search picked.
+    fn expected_reply(&self, column: usize) -> Option<usize> {
+        match self.last_report.as_ref()?.principal_variation() {
+            [best_move, reply, ..] if *best_move == column => Some(*reply),
+            _ => None,
+        }
+    }
+
+    fn stop_pondering(&mut self) {
+        if let Some(ponder) = self.ponder.take() {
+            ponder.stop();
+        }
+    }
+
+    /// Swap the best move for a random other one as often as the config asks.
+    fn maybe_blunder(&mut self, current_board: &Board, best_move: usize) -> usize {
+        if !self.rng.gen_bool(self.config.blunder_chance()) {
+            return best_move;
+        }
+
+        let other_moves: Vec<_> = current_board
+            .list_valid_moves()
+            .into_iter()
+            .filter(|m| *m != best_move)
+            .collect();
+
+        *other_moves.choose(&mut self.rng).unwrap_or(&best_move)
+    }
+
+    fn get_random_move(&mut self, current_board: &Board) -> usize {
+        let moves = current_board.list_valid_moves();
+
+        // SAFETY: A user won't be asked to pick a move if there is a draw.
+        *moves.choose(&mut self.rng).unwrap()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct GameNode {
+    board: Board,
+    color: Square,
+    player_move: usize,
+    evaluation: isize,
+    /// Whether `evaluation` is exact, rather than only showing the node is no better.
+    exact: bool,
+    children: Vec<GameNode>,
+}
+
+/// The node and time limits of one search, shared by every thread searching.
+///
+/// Only the main thread's nodes count towards the node limit, so helper threads add to a search
+/// rather than taking their share of it.
+struct Budget {
+    nodes: AtomicU64,
+    main_nodes: AtomicU64,
+    node_limit: Option<u64>,
+    deadline: Option<Instant>,
+    stop: StopToken,
+    /// Set once the main thread is done, which is when the helpers stop.
+    finished: AtomicBool,
+}
+
+impl Budget {
+    fn new(config: &BotConfig) -> Self {
+        Self {
+            nodes: AtomicU64::new(0),
+            main_nodes: AtomicU64::new(0),
+            node_limit: config.node_budget(),
+            deadline: config.move_time().map(|time| Instant::now() + time),
+            stop: StopToken::new(),
+            finished: AtomicBool::new(false),
+        }
+    }
+
+    /// Also run out when `stop` is used.
+    fn with_stop(mut self, stop: StopToken) -> Self {
+        self.stop = stop;
+        self
+    }
+
+    /// No limits, so the search runs until it is stopped or reaches its depth.
+    fn unlimited() -> Self {
+        Self {
+            nodes: AtomicU64::new(0),
+            main_nodes: AtomicU64::new(0),
+            node_limit: None,
+            deadline: None,
+            stop: StopToken::new(),
+            finished: AtomicBool::new(false),
+        }
+    }
+
+    /// Run out of budget now, whatever is left.
+    fn stop(&self) {
+        self.stop.stop();
+    }
+
+    fn visit(&self, helper: bool) {
+        self.nodes.fetch_add(1, Ordering::Relaxed);
+        if !helper {
+            self.main_nodes.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Nodes visited by every thread.
+    fn nodes(&self) -> u64 {
+        self.nodes.load(Ordering::Relaxed)
+    }
+
+    /// Tell the helpers the main thread is done.
+    fn finish(&self) {
+        self.finished.store(true, Ordering::Relaxed);
+    }
+
+    fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Relaxed)
+    }
+
+    fn exhausted(&self) -> bool {
+        let main_nodes = self.main_nodes.load(Ordering::Relaxed);
+        let out_of_nodes = self.node_limit.is_some_and(|limit| main_nodes >= limit);
+        let out_of_time = self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline);
+
+        out_of_nodes || out_of_time || self.stop.is_stopped()
+    }
+}
+
+struct GameTree<'a> {
+    root: GameNode,
+    evaluator: &'a dyn Evaluator,
+    table: &'a TranspositionTable,
+    ordering: Option<MoveOrdering>,
+    threads: usize,
+    /// Set on the trees of the helper threads, whose results are thrown away.
+    helper: bool,
+    max_depth: usize,
+    /// Depth of the iteration being searched.
+    depth: usize,
+    /// Depth of the last iteration that ran to the end, whose scores the root children hold.
+    completed_depth: usize,
+    /// Set when the budget ran out partway through an iteration.
+    aborted: bool,
+    /// Column of the root child the last completed iteration proved best.
+    best_column: Option<usize>,
+    budget: Arc<Budget>,
+}
+
+/// The scores one iteration gave the root's children.
+struct RootScores {
+    /// Each child's score, and whether it is exact. The search only shows that the others are
+    /// no better than the bound they were searched with.
+    scores: Vec<(isize, bool)>,
+    /// Index of the best child. Its score is always exact.
+    best: usize,
+}
+
+impl<'a> GameTree<'a> {
+    const WIN_SCORE: isize = 1_000_000;
+    const INFINITY: isize = 2 * Self::WIN_SCORE;
+
+    fn new(
+        config: &BotConfig,
+        evaluator: &'a dyn Evaluator,
+        table: &'a TranspositionTable,
+        starting_board: &Board,
+        starting_color: Square,
+    ) -> Self {
+        Self {
+            root: GameNode {
+                board: starting_board.clone(),
+                color: starting_color,
+                player_move: 0, // null here
+                evaluation: evaluator.evaluate(starting_board, starting_color),
+                exact: true,
+                children: vec![],
+            },
+            evaluator,
+            table,
+            ordering: config
+                .move_ordering()
+                .then(|| MoveOrdering::new(starting_board.width())),
+            threads: config.threads(),
+            helper: false,
+            max_depth: config.depth(),
+            depth: 0,
+            completed_depth: 0,
+            aborted: false,
+            best_column: None,
+            budget: Arc::new(Budget::new(config)),
+        }
+    }
+
+    /// A tree for a helper thread to search the same position with. It shares the table and
+    /// the budget, and starts from a copy of the move ordering.
+    fn helper(&self) -> Self {
+        Self {
+            root: GameNode {
+                children: vec![],
+                ..self.root.clone()
+            },
+            evaluator: self.evaluator,
+            table: self.table,
+            ordering: self.ordering.clone(),
+            threads: 1,
+            helper: true,
+            max_depth: self.max_depth,
+            depth: 0,
+            completed_depth: 0,
+            aborted: false,
+            best_column: None,
+            budget: Arc::clone(&self.budget),
+        }
+    }
+
+    fn out_of_budget(&self) -> bool {
+        self.budget.exhausted() || (self.helper && self.budget.is_finished())
+    }
+
+    /// Search every move from the root one ply deeper at a time, writing each child's score
+    /// back into its node.
+    ///
+    /// Each iteration starts from the best move of the one before, and leaves the table primed
+    /// with best moves for the next. When the node or time budget runs out partway through an
+    /// iteration, that iteration is thrown away, so the scores are always those of the last
+    /// depth searched to the end. The first ply is always searched. A full board has no moves
+    /// to search, and keeps the root's own score.
+    ///
+    /// With more than one thread, helper threads search the same position alongside, each one
+    /// ply deeper than the last and starting from a different move. Only the table they fill is
+    /// shared: the main thread finds their results waiting there, and its own are the ones
+    /// played. The helpers stop as soon as it is done.
+    ///
+    /// Like the nodes, the returned score is from the point of view of the player who made the
+    /// move into the root.
+    fn search(&mut self) -> isize {
+        self.table.new_search();
+        self.expand_root();
+
+        let mut root_children = std::mem::take(&mut self.root.children);
+        if root_children.is_empty() {
+            return self.root.evaluation;
+        }
+
+        let best = if self.threads > 1 {
+            let helpers: Vec<_> = (1..self.threads)
+                .map(|i| {
+                    let mut children = root_children.clone();
+                    children.rotate_left(i % root_children.len());
+                    (i, self.helper(), children)
+                })
+                .collect();
+
+            thread::scope(|scope| {
+                for (i, mut helper, mut children) in helpers {
+                    scope.spawn(move || helper.deepen(&mut children, 1 + i % 2));
+                }
+
+                let best = self.deepen(&mut root_children, 1);
+                self.budget.finish();
+                best
+            })
+        } else {
+            self.deepen(&mut root_children, 1)
+        };
+
+        root_children.sort_by_key(|child| child.player_move);
+        self.root.children = root_children;
+        self.root.evaluation = -best;
+
+        -best
+    }
+
+    /// The iterations of `search` from `first_depth` on, returning the best child's score.
+    fn deepen(&mut self, root_children: &mut [GameNode], first_depth: usize) -> isize {
+        // Searching past the end of the game gives the same scores again.
+        let board = &self.root.board;
+        let empty_cells = board.width() * board.height() - board.moves_played();
+        let max_depth = self.max_depth.min(empty_cells).max(1);
+
+        let mut best = -Self::INFINITY;
+
+        for depth in first_depth.min(max_depth)..=max_depth {
+            self.depth = depth;
+
+            let Some(root_scores) = self.search_root(root_children) else {
+                break;
+            };
+
+            for (child, (evaluation, exact)) in root_children.iter_mut().zip(root_scores.scores) {
+                child.evaluation = evaluation;
+                child.exact = exact;
+            }
+            best = root_children[root_scores.best].evaluation;
+            self.best_column = Some(root_children[root_scores.best].player_move);
+            self.completed_depth = depth;
+
+            // Best first, so the next iteration has a tight bound from its first move. The other
+            // scores are mostly bounds, which say nothing about how the rest compare, so they
+            // keep their order.
+            root_children[..=root_scores.best].rotate_right(1);
+
+            let decided = best.abs() > Self::WIN_SCORE / 2;
+            if decided || self.out_of_budget() {
+                break;
+            }
+        }
+
+        best
+    }
+
+    /// One iteration over the root's children, or `None` if it was cut short.
+    ///
+    /// The best child is the first to beat every score before it, so of two equally good
+    /// moves the earlier one is played.
+    fn search_root(&mut self, root_children: &[GameNode]) -> Option<RootScores> {
+        let mut alpha = -Self::INFINITY;
+        let mut scores = vec![];
+        let mut best = 0;
+
+        for (i, child) in root_children.iter().enumerate() {
+            let evaluation = self.search_root_child(child, alpha);
+
+            if self.aborted {
+                return None;
+            }
+
+            // Only a score above the bound is exact. One at or below it may be too high.
+            let exact = evaluation > alpha;
+            if exact {
+                alpha = evaluation;
+                best = i;
+            }
+            scores.push((evaluation, exact));
+        }
+
+        Some(RootScores { scores, best })
+    }
+
+    /// Score of one of the root's children, searched with the bound set by its older siblings.
+    fn search_root_child(&mut self, child: &GameNode, alpha: isize) -> isize {
+        -self.negamax(
+            &child.board,
+            child.color.flip_into(),
+            1,
+            -Self::INFINITY,
+            -alpha,
+        )
+    }
+
+    /// Alpha-beta search of `board` with `color` to move, scored for `color`.
+    fn negamax(
+        &mut self,
+        board: &Board,
+        color: Square,
+        tree_depth: usize,
+        mut alpha: isize,
+        mut beta: isize,
+    ) -> isize {
+        self.budget.visit(self.helper);
+
+        // The first iteration only scores the root's children, so it always finishes.
+        if self.depth > 1 && self.out_of_budget() {
+            self.aborted = true;
+        }
+        if self.aborted {
+            return 0;
+        }
+
+        if let Some(result) = board.check_for_win() {
+            // Prefer quick wins and slow losses.
+            let win_score = Self::WIN_SCORE - tree_depth as isize;
+            return match result {
+                GameResult::Win(winner, _) if winner == color => win_score,
+                GameResult::Win(..) => -win_score,
+                GameResult::Draw => 0,
+            };
+        }
+
+        if tree_depth >= self.depth {
+            return self.evaluator.evaluate(board, color);
+        }
+
+        // Same score the winning child would get, without expanding anything.
+        if !board.winning_moves(color).is_empty() {
+            return Self::WIN_SCORE - (tree_depth + 1) as isize;
+        }
+
+        let depth = self.depth - tree_depth;
+        let key = TranspositionTable::key(board, color);
+        let original_alpha = alpha;
+        let mut table_move = None;
+
+        if let Some(entry) = self.table.probe(key) {
+            table_move = entry
+                .best_move
+                .map(|m| TranspositionTable::orient(board, m));
+
+            if entry.depth >= depth {
+                let score = Self::score_from_table(entry.score, tree_depth);
+                match entry.bound {
+                    Bound::Exact => return score,
+                    Bound::Lower => alpha = alpha.max(score),
+                    Bound::Upper => beta = beta.min(score),
+                }
+                if alpha >= beta {
+                    return score;
+                }
+            }
+        }
+
+        let moves = self.order_moves(board, color, tree_depth, table_move);
+
+        let mut best = -Self::INFINITY;
+        let mut best_move = None;
+
+        for m in moves {
+            let mut child = board.clone();
+            child.apply_move(m, color).unwrap();
+
+            let evaluation =
+                -self.negamax(&child, color.flip_into(), tree_depth + 1, -beta, -alpha);
+
+            if self.aborted {
+                return 0;
+            }
+
+            if evaluation > best {
+                best = evaluation;
+                best_move = Some(m);
+            }
+
+            alpha = alpha.max(evaluation);
+            if alpha >= beta {
+                if let Some(ordering) = &mut self.ordering {
+                    ordering.record_cutoff(color, tree_depth, m, depth);
+                }
+                break;
+            }
+        }
+
+        let bound = if best <= original_alpha {
+            Bound::Upper
+        } else if best >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+
+        self.table.store(Entry::new(
+            key,
+            depth,
+            bound,
+            Self::score_to_table(best, tree_depth),
+            best_move.map(|m| TranspositionTable::orient(board, m)),
+        ));
+
+        best
+    }
+
+    fn order_moves(
+        &self,
+        board: &Board,
+        color: Square,
+        tree_depth: usize,
+        table_move: Option<usize>,
+    ) -> Vec<usize> {
+        if let Some(ordering) = &self.ordering {
+            return ordering.order(board, color, tree_depth, table_move);
+        }
+
+        let mut moves = board.list_valid_moves();
+
+        // The best move from an earlier visit is the most likely to cut off again.
+        if let Some(i) = moves.iter().position(|m| Some(*m) == table_move) {
+            moves[..=i].rotate_right(1);
+        }
+
+        moves
+    }
+
+    /// What the search found, from the point of view of the player to move at the root.
+    fn report(&self, elapsed: std::time::Duration) -> SearchReport {
+        let column_scores = self
+            .root
+            .children
+            .iter()
+            .map(|child| ColumnScore {
+                column: child.player_move,
+                score: child.evaluation,
+                bound: if child.exact {
+                    ScoreBound::Exact
+                } else {
+                    ScoreBound::Upper
+                },
+            })
+            .collect();
+
+        SearchReport::new(
+            self.completed_depth,
+            self.budget.nodes(),
+            elapsed,
+            -self.root.evaluation,
+            self.principal_variation(),
+            column_scores,
+        )
+    }
+
+    /// The best move, followed by the best moves the table holds for the positions after it,
+    /// up to the depth searched.
+    fn principal_variation(&self) -> Vec<usize> {
+        let Some(best_move) = self.best_move() else {
+            return vec![];
+        };
+
+        let mut board = self.root.board.clone();
+        let mut line = vec![];
+        let mut next = Some(best_move);
+
+        while let Some(column) = next {
+            if line.len() >= self.completed_depth.max(1)
+                || board.check_for_win().is_some()
+                || !board.is_valid_move(column)
+            {
+                break;
+            }
+
+            // SAFETY: The move was checked above.
+            board.apply_move(column, board.side_to_move()).unwrap();
+            line.push(column);
+
+            next = self
+                .table
+                .probe(TranspositionTable::key(&board, board.side_to_move()))
+                .and_then(|entry| entry.best_move)
+                .map(|column| TranspositionTable::orient(&board, column));
+        }
+
+        line
+    }
+
+    /// The column of the root's best child, once the tree has been searched.
+    fn best_move(&self) -> Option<usize> {
+        self.best_column
+    }
+
+    /// Win scores count plies from the root. The table stores them counted from the position
+    /// instead, so they stay correct when the position is reached at a different depth.
+    fn score_to_table(score: isize, tree_depth: usize) -> isize {
+        match score {
+            s if s > Self::WIN_SCORE / 2 => s + tree_depth as isize,
+            s if s < -Self::WIN_SCORE / 2 => s - tree_depth as isize,
+            s => s,
+        }
+    }
+
+    fn score_from_table(score: isize, tree_depth: usize) -> isize {
+        match score {
+            s if s > Self::WIN_SCORE / 2 => s - tree_depth as isize,
+            s if s < -Self::WIN_SCORE / 2 => s + tree_depth as isize,
+            s => s,
+        }
+    }
+
+    fn expand_root(&mut self) {
+        let color = self.root.color.flip_into();
+        let moves = self.order_moves(&self.root.board, color, 0, None);
+        let mut children = Vec::new();
+
+        for m in moves {
+            let mut board = self.root.board.clone();
+
+            board.apply_move(m, color).unwrap();
+
+            children.push(GameNode {
+                evaluation: self.evaluator.evaluate(&board, color),
+                exact: true,
+                board,
+                color,
+                player_move: m,
+                children: vec![],
+            });
+        }
+
+        self.root.children = children;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::{sync::atomic::AtomicUsize, time::Duration};
+
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::vertical(&[
+        "_______",
+        "_______",
+        "_______",
+        "__R____",
+        "__R_Y__",
+        "__R_YY_",
+    ], Square::Red, 2)]
+    #[case::horizontal(&[
+        "_______",
+        "_______",
+        "_______",
+        "_______",
+        "___R___",
+        "R_RYYY_",
+    ], Square::Yellow, 6)]
+    #[case::diagonal(&[
+        "_______",
+        "_______",
+        "___Y___",
+        "__YR___",
+        "_YRR___",
+        "_YRY___",
+    ], Square::Yellow, 0)]
+    fn test_takes_immediate_win(
+        #[case] data: &[&str; 6],
+        #[case] color: Square,
+        #[case] expected: usize,
+    ) {
+        let board = Board::new_from_str_vec(data);
+        let mut bot = Bot::new(color);
+
+        assert_eq!(bot.get_move(&board).unwrap(), expected);
+    }
+
+    #[rstest]
+    #[case::vertical(&[
+        "_______",
+        "_______",
+        "_______",
+        "____Y__",
+        "____Y__",
+        "R_R_Y__",
+    ], Square::Red, 4)]
+    #[case::horizontal(&[
+        "_______",
+        "_______",
+        "_______",
+        "_______",
+        "_______",
+        "YR_RR_Y",
+    ], Square::Yellow, 2)]
+    fn test_blocks_immediate_loss(
+        #[case] data: &[&str; 6],
+        #[case] color: Square,
+        #[case] expected: usize,
+    ) {
+        let board = Board::new_from_str_vec(data);
+        let mut bot = Bot::new(color);
+
+        assert_eq!(bot.get_move(&board).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_prefers_win_over_block() {
+        let board = Board::new_from_str_vec(&[
+            "_______", "_______", "_______", "R___Y__", "R___Y__", "R_Y_Y__",
+        ]);
+        let mut bot = Bot::new(Square::Red);
+
+        assert_eq!(bot.get_move(&board).unwrap(), 0);
+    }
+
+    /// Columns 1 and 5 come back with the winning score too, but only as bounds: column 2 is
+    /// the only forced win.
+    #[rstest]
+    fn test_plays_proven_win_over_tied_bounds(
+        #[values(Difficulty::Medium, Difficulty::Hard)] difficulty: Difficulty,
+        #[values(1, 4)] threads: usize,
+    ) {
+        let board: Board = "_______/_______/_______/_______/______R/___YY_R Y 5"
+            .parse()
+            .unwrap();
+        let config = BotConfig::new(difficulty).with_threads(threads);
+        let mut bot = Bot::with_config(Square::Yellow, config);
+
+        assert_eq!(bot.get_move(&board).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_search_propagates_scores_to_root() {
+        let board = Board::new_from_str_vec(&[
+            "_______", "_______", "_______", "__R____", "__R_Y__", "__R_YY_",
+        ]);
+        let config = BotConfig::new(Difficulty::Easy).with_depth(3);
+        let table = TranspositionTable::new(config.table_capacity());
+        let mut tree = GameTree::new(&config, &ThreatEvaluator, &table, &board, Square::Yellow);
+
+        let evaluation = tree.search();
+
+        let root = &tree.root;
+        let winning_child = root.children.iter().find(|c| c.player_move == 2).unwrap();
+
+        assert_eq!(winning_child.evaluation, GameTree::WIN_SCORE - 1);
+        assert_eq!(root.evaluation, -(GameTree::WIN_SCORE - 1));
+        assert_eq!(evaluation, root.evaluation);
+    }
+
+    #[test]
+    fn test_blunder_chance_skips_best_move() {
+        let board = Board::new_from_str_vec(&[
+            "_______", "_______", "_______", "__R____", "__R_Y__", "__R_YY_",
+        ]);
+        let config = BotConfig::new(Difficulty::Easy).with_blunder_chance(1.0);
+        let mut bot = Bot::with_config(Square::Red, config);
+
+        for _ in 0..10 {
+            assert_ne!(bot.get_move(&board).unwrap(), 2);
+        }
+    }
+
+    #[test]
+    fn test_node_budget_limits_search() {
+        let board = Board::new_from_str_vec(&[
+            "_______", "_______", "_______", "_______", "_______", "___Y___",
+        ]);
+        let config = BotConfig::new(Difficulty::Perfect).with_node_budget(100);
+        let table = TranspositionTable::new(config.table_capacity());
+        let mut tree = GameTree::new(&config, &ThreatEvaluator, &table, &board, Square::Yellow);
+
+        let evaluation = tree.search();
+
+        // The iteration the budget runs out in stops on the node that spends it.
+        assert_eq!(tree.budget.nodes(), 100);
+        assert!(tree.aborted);
+        assert!(tree.completed_depth >= 1);
+
+        // The scores are those of the last iteration that finished.
+        let depth = tree.completed_depth;
+        let best_move = tree.best_move();
+
+        let config = BotConfig::new(Difficulty::Perfect).with_depth(depth);
+        let table = TranspositionTable::new(config.table_capacity());
+        let mut tree = GameTree::new(&config, &ThreatEvaluator, &table, &board, Square::Yellow);
+
+        assert_eq!(tree.search(), evaluation);
+        assert_eq!(tree.best_move(), best_move);
+    }
+
+    /// The helpers leave deeper results in the table, so the scores can differ from a single
+    /// thread's, but the move played is always one the main thread proved best.
+    #[rstest]
+    #[case::opening("4435")]
+    #[case::middlegame("44433526")]
+    #[case::tactics("2246237261")]
+    fn test_threads_play_proven_move(#[case] moves: &str) {
+        let board = Board::from_moves(moves).unwrap();
+        let color = board.side_to_move().flip_into();
+        let config = BotConfig::new(Difficulty::Hard).with_threads(4);
+        let table = TranspositionTable::new(config.table_capacity());
+        let mut tree = GameTree::new(&config, &ThreatEvaluator, &table, &board, color);
+
+        let evaluation = tree.search();
+
+        let best_move = tree.best_move().unwrap();
+        let best = tree
+            .root
+            .children
+            .iter()
+            .find(|c| c.player_move == best_move)
+            .unwrap();
+        assert!(best.exact);
+        assert_eq!(best.evaluation, -evaluation);
+        assert!(tree
+            .root
+            .children
+            .iter()
+            .all(|c| c.evaluation <= best.evaluation));
+        assert_eq!(tree.completed_depth, config.depth());
+    }
+
+    /// Only worth running on a machine with several cores, in a release build:
+    /// `cargo test --release -- --ignored`.
+    #[test]
+    #[ignore = "needs several cores"]
+    fn test_threads_search_deeper() {
+        let board = Board::from_moves("4435").unwrap();
+        let depth = |threads| {
+            let config = BotConfig::new(Difficulty::Perfect)
+                .with_node_budget(u64::MAX)
+                .with_move_time(Duration::from_secs(2))
+                .with_threads(threads);
+            let mut bot = Bot::with_config(Square::Yellow, config);
+            bot.get_move(&board).unwrap();
+            bot.last_report().unwrap().depth()
+        };
+        let cores = thread::available_parallelism().map_or(1, |n| n.get());
+        assert!(cores > 1, "only one core");
+
+        assert!(depth(cores) > depth(1));
+    }
+
+    #[test]
+    fn test_single_thread_is_deterministic() {
+        let board = Board::from_moves("443526").unwrap();
+        let config = BotConfig::new(Difficulty::Hard).with_depth(7);
+
+        let mut first = Bot::with_config(Square::Yellow, config.clone());
+        let mut second = Bot::with_config(Square::Yellow, config);
+
+        assert_eq!(
+            first.get_move(&board).unwrap(),
+            second.get_move(&board).unwrap()
+        );
+        assert_eq!(first.nodes_searched(), second.nodes_searched());
+    }
+
+    #[test]
+    fn test_threads_stop_at_move_time() {
+        let board = Board::from_moves("4").unwrap();
+        let config = BotConfig::new(Difficulty::Perfect)
+            .with_move_time(Duration::from_millis(200))
+            .with_threads(4);
+        let mut bot = Bot::with_config(Square::Red, config);
+
+        let start = Instant::now();
+        let column = bot.get_move(&board).unwrap();
+
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(board.is_valid_move(column));
+        assert!(bot.nodes_searched() > 0);
+    }
+
+    /// Fails every report, like a sink writing to a closed pipe.
+    struct BrokenSink;
+
+    impl ReportSink for BrokenSink {
+        fn report(&mut self, _: &SearchReport) -> std::io::Result<()> {
+            Err(std::io::ErrorKind::BrokenPipe.into())
+        }
+    }
+
+    #[test]
+    fn test_failed_report_still_plays() {
+        let board = Board::from_moves("4435").unwrap();
+        let mut bot = Bot::with_config(Square::Red, BotConfig::new(Difficulty::Medium))
+            .with_report_sink(BrokenSink);
+
+        assert!(bot.get_move(&board).is_ok());
+        assert!(bot.last_report().is_some());
+    }
+
+    #[test]
+    fn test_report() {
+        let board = Board::from_moves("4435").unwrap();
+        let reports = Arc::new(std::sync::Mutex::new(vec![]));
+        let sink = reports.clone();
+
+        let mut bot = Bot::with_config(Square::Red, BotConfig::new(Difficulty::Medium))
+            .with_report_sink(move |report: &SearchReport| {
+                sink.lock().unwrap().push(report.clone());
+            });
+        let column = bot.get_move(&board).unwrap();
+
+        let report = bot.last_report().unwrap();
+        assert_eq!(
+            reports.lock().unwrap().as_slice(),
+            std::slice::from_ref(report)
+        );
+        assert_eq!(report.depth(), 4);
+        assert_eq!(report.nodes(), bot.nodes_searched());
+        assert_eq!(report.best_move(), Some(column));
+        assert_eq!(report.column_scores().len(), 7);
+
+        let best = report.column_scores().iter().map(|c| c.score).max();
+        assert_eq!(Some(report.score()), best);
+
+        let best_column = report
+            .column_scores()
+            .iter()
+            .find(|c| c.column == column)
+            .unwrap();
+        assert_eq!(best_column.score, report.score());
+        assert_eq!(best_column.bound, ScoreBound::Exact);
+
+        // The line is a sequence of legal moves from the position searched.
+        let pv = report.principal_variation();
+        assert!(!pv.is_empty() && pv.len() <= 4);
+        let mut line = board.clone();
+        for column in pv {
+            line.apply_move(*column, line.side_to_move()).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_report_of_forced_win() {
+        let board = Board::new_from_str_vec(&[
+            "_______", "_______", "_______", "__R____", "__R_Y__", "__R_YY_",
+        ]);
+        let mut bot = Bot::new(Square::Red);
+
+        bot.get_move(&board).unwrap();
+
+        let report = bot.last_report().unwrap();
+        assert_eq!(report.plies_to_end(), Some(1));
+        assert!(report.score() > 0);
+        assert_eq!(report.principal_variation(), [2]);
+    }
+
+    #[test]
+    fn test_unsearched_moves_are_not_reported() {
+        let mut bot = Bot::new(Square::Yellow);
+
+        bot.get_move(&Board::new()).unwrap();
+
+        assert_eq!(bot.last_report(), None);
+    }
+
+    /// The bot's move from `board`, played and announced to it.
+    fn play_own_move(bot: &mut Bot, board: &mut Board) -> usize {
+        let column = bot.get_move(board).unwrap();
+        board.apply_move(column, board.side_to_move()).unwrap();
+        bot.notify_move(board, column);
+
+        column
+    }
+
+    #[test]
+    fn test_ponders_expected_reply() {
+        let mut board = Board::from_moves("4435").unwrap();
+        let config = BotConfig::new(Difficulty::Hard).with_pondering(true);
+        let mut bot = Bot::with_config(Square::Yellow, config);
+
+        play_own_move(&mut bot, &mut board);
+
+        let reply = bot.last_report().unwrap().principal_variation()[1];
+        let mut expected = board.clone();
+        expected.apply_move(reply, Square::Red).unwrap();
+        assert_eq!(bot.pondering(), Some(&expected));
+
+        // Any reply stops it.
+        let column = board.list_valid_moves()[0];
+        board.apply_move(column, Square::Red).unwrap();
+        bot.notify_move(&board, column);
+
+        assert_eq!(bot.pondering(), None);
+    }
+
+    #[test]
+    fn test_pondering_warms_table() {
+        let config = BotConfig::new(Difficulty::Hard).with_depth(8);
+        let mut pondering = Bot::with_config(Square::Yellow, config.clone().with_pondering(true));
+        let mut waiting = Bot::with_config(Square::Yellow, config);
+
+        let mut board = Board::from_moves("4435").unwrap();
+        let column = play_own_move(&mut pondering, &mut board);
+        assert_eq!(
+            play_own_move(&mut waiting, &mut Board::from_moves("4435").unwrap()),
+            column
+        );
+
+        // Let the search reach its depth, then play the reply it expected.
+        let expected = pondering.pondering().unwrap().clone();
+        pondering.ponder.take().unwrap().finish();
+
+        pondering.get_move(&expected).unwrap();
+        waiting.get_move(&expected).unwrap();
+
+        assert!(pondering.nodes_searched() < waiting.nodes_searched() / 2);
+    }
+
+    #[test]
+    fn test_pondering_is_opt_in() {
+        let mut board = Board::from_moves("4435").unwrap();
+        let mut bot = Bot::new(Square::Yellow);
+
+        play_own_move(&mut bot, &mut board);
+
+        assert_eq!(bot.pondering(), None);
+    }
+
+    #[test]
+    fn test_no_pondering_after_the_game() {
+        let mut board = Board::new_from_str_vec(&[
+            "_______", "_______", "_______", "_______", "___R___", "R_RYYY_",
+        ]);
+        let config = BotConfig::new(Difficulty::Medium).with_pondering(true);
+        let mut bot = Bot::with_config(Square::Yellow, config);
+
+        play_own_move(&mut bot, &mut board);
+
+        assert!(board.check_for_win().is_some());
+        assert_eq!(bot.pondering(), None);
+    }
+
+    #[test]
+    fn test_no_pondering_when_reply_ends_game() {
+        let mut board = Board::with_dimensions(3, 1, 3).unwrap();
+        board.apply_move(0, Square::Yellow).unwrap();
+        let config = BotConfig::new(Difficulty::Medium).with_pondering(true);
+        let mut bot = Bot::with_config(Square::Red, config);
+
+        play_own_move(&mut bot, &mut board);
+
+        assert_eq!(bot.pondering(), None);
+    }
+
+    #[test]
+    fn test_search_without_moves() {
+        let mut board = Board::with_dimensions(3, 1, 3).unwrap();
+        board.play_moves("123").unwrap();
+        let config = BotConfig::new(Difficulty::Medium);
+        let table = TranspositionTable::new(config.table_capacity());
+        let mut tree = GameTree::new(&config, &ThreatEvaluator, &table, &board, Square::Yellow);
+
+        tree.search();
+
+        assert_eq!(tree.best_move(), None);
+    }
+
+    #[test]
+    fn test_stop_token_interrupts_search() {
+        let board = Board::from_moves("4").unwrap();
+        let mut bot = Bot::with_config(Square::Red, BotConfig::new(Difficulty::Perfect));
+        let stop = StopToken::new();
+
+        let start = Instant::now();
+        let column = thread::scope(|scope| {
+            let handle = stop.clone();
+            scope.spawn(move || {
+                thread::sleep(Duration::from_millis(100));
+                handle.stop();
+            });
+
+            bot.get_move_with_stop(&board, &stop).unwrap()
+        });
+
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(board.is_valid_move(column));
+        assert!(bot.last_report().unwrap().depth() >= 1);
+        assert!(stop.is_stopped());
+    }
+
+    #[test]
+    fn test_stopped_token_still_searches_first_ply() {
+        let board = Board::new_from_str_vec(&[
+            "_______", "_______", "_______", "____Y__", "____Y__", "R_R_Y__",
+        ]);
+        let mut bot = Bot::with_config(Square::Red, BotConfig::new(Difficulty::Perfect));
+        let stop = StopToken::new();
+        stop.stop();
+
+        assert_eq!(bot.get_move_with_stop(&board, &stop).unwrap(), 4);
+        assert_eq!(bot.last_report().unwrap().depth(), 1);
+    }
+
+    #[test]
+    fn test_stop_only_applies_to_running_search() {
+        let board = Board::from_moves("4435").unwrap();
+        let stop = StopToken::new();
+        let mut bot = Bot::new(Square::Yellow).with_stop_token(stop.clone());
+
+        stop.stop();
+        bot.get_move(&board).unwrap();
+
+        assert_eq!(bot.last_report().unwrap().depth(), bot.config().depth());
+        assert!(!bot.stop_token().is_stopped());
+    }
+
+    #[test]
+    fn test_same_seed_same_random_choices() {
+        let board = Board::from_moves("4435").unwrap();
+        let config = BotConfig::new(Difficulty::Easy).with_blunder_chance(0.5);
+        let moves = |seed| {
+            let mut bot = Bot::with_config(Square::Yellow, config.clone()).with_seed(seed);
+            let mut moves = vec![bot.get_move(&Board::new()).unwrap()];
+            moves.extend((0..10).map(|_| bot.get_move(&board).unwrap()));
+            moves
+        };
+
+        assert_eq!(moves(5), moves(5));
+        assert_eq!(Bot::new(Square::Red).with_seed(5).seed(), Some(5));
+    }
+
+    #[test]
+    fn test_zero_budget_still_searches_first_ply() {
+        let board = Board::new_from_str_vec(&[
+            "_______", "_______", "_______", "____Y__", "____Y__", "R_R_Y__",
+        ]);
+        let config = BotConfig::new(Difficulty::Hard).with_node_budget(0);
+        let mut bot = Bot::with_config(Square::Red, config);
+
+        // Only the first ply is searched, and every other move leaves a win for Yellow.
+        assert_eq!(bot.get_move(&board).unwrap(), 4);
+    }
+
+    #[test]
+    fn test_move_time_is_kept() {
+        let config = BotConfig::new(Difficulty::Perfect).with_move_time(Duration::from_millis(200));
+        let mut bot = Bot::with_config(Square::Red, config);
+        let board = Board::from_moves("4").unwrap();
+
+        let start = Instant::now();
+        let best_move = bot.get_move(&board).unwrap();
+
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(board.is_valid_move(best_move));
+    }
+
+    #[test]
+    fn test_stops_deepening_once_decided() {
+        let board = Board::new_from_str_vec(&[
+            "_______", "_______", "_______", "__R____", "__R_Y__", "__R_YY_",
+        ]);
+        let config = BotConfig::new(Difficulty::Perfect);
+        let table = TranspositionTable::new(config.table_capacity());
+        let mut tree = GameTree::new(&config, &ThreatEvaluator, &table, &board, Square::Yellow);
+
+        tree.search();
+
+        assert_eq!(tree.best_move(), Some(2));
+        assert!(tree.completed_depth <= 2);
+    }
+
+    #[test]
+    fn test_table_is_reused_between_searches() {
+        let board = Board::new_from_str_vec(&[
+            "_______", "_______", "_______", "_______", "___R___", "__YYR__",
+        ]);
+        let config = BotConfig::new(Difficulty::Medium);
+        let table = TranspositionTable::new(config.table_capacity());
+
+        let mut tree = GameTree::new(&config, &ThreatEvaluator, &table, &board, Square::Red);
+        let first_evaluation = tree.search();
+        let first_nodes = tree.budget.nodes();
+
+        let mut tree = GameTree::new(&config, &ThreatEvaluator, &table, &board, Square::Red);
+        let second_evaluation = tree.search();
+
+        // The table can change which of two equally good moves is searched, and so played, first.
+        let best_move = tree.best_move().unwrap();
+        let best_child = tree
+            .root
+            .children
+            .iter()
+            .find(|c| c.player_move == best_move);
+
+        assert_eq!(second_evaluation, first_evaluation);
+        assert_eq!(best_child.unwrap().evaluation, -second_evaluation);
+        assert!(tree.budget.nodes() < first_nodes);
+    }
+
+    #[test]
+    fn test_tiny_table_still_finds_block() {
+        let board = Board::new_from_str_vec(&[
+            "_______", "_______", "_______", "____Y__", "____Y__", "R_R_Y__",
+        ]);
+        let config = BotConfig::new(Difficulty::Hard).with_table_capacity(1);
+        let mut bot = Bot::with_config(Square::Red, config);
+
+        assert_eq!(bot.get_move(&board).unwrap(), 4);
+    }
+
+    #[test]
+    fn test_plays_on_other_dimensions() {
+        let mut board = Board::with_dimensions(5, 4, 3).unwrap();
+        board.apply_move(0, Square::Yellow).unwrap();
+        board.apply_move(4, Square::Red).unwrap();
+        board.apply_move(1, Square::Yellow).unwrap();
+
+        let mut bot = Bot::new(Square::Red);
+
+        // Yellow threatens three in a row on the bottom row.
+        assert_eq!(bot.get_move(&board).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_mirrored_position_mirrors_move() {
+        let board = Board::new_from_str_vec(&[
+            "_______", "_______", "_______", "____Y__", "____Y__", "R_R_Y__",
+        ]);
+        let mut bot = Bot::new(Square::Red);
+
+        assert_eq!(bot.get_move(&board).unwrap(), 4);
+        // The table now holds the position's entries under the canonical keys.
+        assert_eq!(bot.get_move(&board.mirrored()).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_with_evaluator() {
+        let board = Board::new_from_str_vec(&[
+            "_______", "_______", "_______", "____Y__", "____Y__", "R_R_Y__",
+        ]);
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+
+        let mut bot = Bot::new(Square::Red).with_evaluator(move |_: &Board, _: Square| {
+            counter.fetch_add(1, Ordering::Relaxed);
+            0
+        });
+
+        assert_eq!(bot.get_move(&board).unwrap(), 4);
+        assert!(calls.load(Ordering::Relaxed) > 0);
+    }
+
+    #[test]
+    fn test_window_evaluator_blocks() {
+        let board = Board::new_from_str_vec(&[
+            "_______", "_______", "_______", "_______", "_______", "RYYY_R_",
+        ]);
+        let mut bot = Bot::new(Square::Red).with_evaluator(WindowEvaluator::default());
+
+        assert_eq!(bot.get_move(&board).unwrap(), 4);
+    }
+
+    #[test]
+    fn test_move_ordering_searches_fewer_nodes() {
+        let board = Board::from_moves("4435").unwrap();
+        let config = BotConfig::new(Difficulty::Hard).with_depth(8);
+
+        let mut ordered = Bot::with_config(Square::Red, config.clone());
+        let mut unordered = Bot::with_config(Square::Red, config.with_move_ordering(false));
+
+        assert_eq!(
+            ordered.get_move(&board).unwrap(),
+            unordered.get_move(&board).unwrap()
+        );
+        assert!(ordered.nodes_searched() > 0);
+        assert!(ordered.nodes_searched() < unordered.nodes_searched());
+    }
+
+    #[test]
+    fn test_plays_from_book() {
+        let root = Board::from_moves("2246237261472515267444").unwrap();
+        let book = OpeningBook::generate(&root, root.moves_played(), &mut Solver::new(), |_, _| {})
+            .unwrap();
+        let best_move = book.lookup(&root).unwrap().best_move();
+
+        let mut bot = Bot::new(root.side_to_move()).with_book(book);
+
+        assert_eq!(bot.get_move(&root).unwrap(), best_move);
+        assert_eq!(bot.nodes_searched(), 0);
+    }
+}
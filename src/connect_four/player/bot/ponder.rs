@@ -0,0 +1,65 @@
+use std::{
+    sync::Arc,
+    thread::{self, JoinHandle},
+};
+
+use crate::connect_four::board::Board;
+
+use super::{transposition::TranspositionTable, BotConfig, Budget, Evaluator, GameTree};
+
+/// A search running in the background while the opponent thinks.
+///
+/// It searches the position the bot expects to be in once the opponent has replied, with no
+/// budget, until it is stopped or reaches the configured depth. Nothing it finds is used
+/// directly: the point is the entries it leaves in the shared table, which the real search
+/// then finds waiting.
+pub(super) struct Ponder {
+    board: Board,
+    budget: Arc<Budget>,
+    handle: JoinHandle<()>,
+}
+
+impl Ponder {
+    pub(super) fn start(
+        config: &BotConfig,
+        evaluator: Arc<dyn Evaluator>,
+        table: Arc<TranspositionTable>,
+        board: Board,
+    ) -> Self {
+        let budget = Arc::new(Budget::unlimited());
+        let config = config.clone();
+        let position = board.clone();
+        let tree_budget = Arc::clone(&budget);
+
+        let handle = thread::spawn(move || {
+            let color = position.side_to_move().flip_into();
+            let mut tree = GameTree::new(&config, evaluator.as_ref(), &table, &position, color);
+            tree.budget = tree_budget;
+            tree.search();
+        });
+
+        Self {
+            board,
+            budget,
+            handle,
+        }
+    }
+
+    /// The position being searched.
+    pub(super) fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Stop the search and wait for its thread, which only takes as long as the node it is on.
+    pub(super) fn stop(self) {
+        self.budget.stop();
+        self.finish();
+    }
+
+    /// Wait for the search to end by itself.
+    pub(super) fn finish(self) {
+        // Pondering only warms the table, so a search that panicked costs nothing but the
+        // entries it would have left. The game carries on without them.
+        let _ = self.handle.join();
+    }
+}
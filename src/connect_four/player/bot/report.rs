@@ -0,0 +1,328 @@
+use std::{
+    fmt::{Display, Formatter},
+    io::Write,
+    time::Duration,
+};
+
+use super::GameTree;
+
+/// What the bot found on one search.
+///
+/// Scores are from the bot's point of view and in the search's units, so a win is worth close
+/// to a million.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchReport {
+    depth: usize,
+    nodes: u64,
+    elapsed: Duration,
+    score: isize,
+    principal_variation: Vec<usize>,
+    column_scores: Vec<ColumnScore>,
+}
+
+/// What the search found for one column.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ColumnScore {
+    pub column: usize,
+    pub score: isize,
+    pub bound: ScoreBound,
+}
+
+/// How far a column's score can be trusted. Alpha-beta only shows that most columns are no
+/// better than the best one found so far, and stops there.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ScoreBound {
+    /// The column's score.
+    Exact,
+    /// The column's score is at most this.
+    Upper,
+}
+
+impl SearchReport {
+    pub(super) fn new(
+        depth: usize,
+        nodes: u64,
+        elapsed: Duration,
+        score: isize,
+        principal_variation: Vec<usize>,
+        column_scores: Vec<ColumnScore>,
+    ) -> Self {
+        Self {
+            depth,
+            nodes,
+            elapsed,
+            score,
+            principal_variation,
+            column_scores,
+        }
+    }
+
+    /// Plies of the deepest iteration searched to the end.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Nodes searched per second.
+    pub fn nps(&self) -> u64 {
+        match self.elapsed.as_secs_f64() {
+            secs if secs > 0.0 => (self.nodes as f64 / secs) as u64,
+            _ => 0,
+        }
+    }
+
+    pub fn score(&self) -> isize {
+        self.score
+    }
+
+    /// Plies left in the game when the search found a forced result. The score's sign says
+    /// who wins.
+    pub fn plies_to_end(&self) -> Option<usize> {
+        (self.score.abs() > GameTree::WIN_SCORE / 2)
+            .then(|| (GameTree::WIN_SCORE - self.score.abs()) as usize)
+    }
+
+    /// The best move, then the best replies to it as far as the table remembers them.
+    pub fn principal_variation(&self) -> &[usize] {
+        &self.principal_variation
+    }
+
+    pub fn best_move(&self) -> Option<usize> {
+        self.principal_variation.first().copied()
+    }
+
+    /// Every column searched, left to right, with its score. The best column's score is always
+    /// exact.
+    pub fn column_scores(&self) -> &[ColumnScore] {
+        &self.column_scores
+    }
+
+    /// The report as one line of JSON.
+    pub fn to_json(&self) -> String {
+        let list = |items: Vec<String>| items.join(",");
+
+        let plies_to_end = match self.plies_to_end() {
+            Some(plies) => plies.to_string(),
+            None => "null".to_string(),
+        };
+        let principal_variation = list(
+            self.principal_variation
+                .iter()
+                .map(|column| column.to_string())
+                .collect(),
+        );
+        let column_scores = list(
+            self.column_scores
+                .iter()
+                .map(|c| {
+                    let bound = match c.bound {
+                        ScoreBound::Exact => "exact",
+                        ScoreBound::Upper => "upper",
+                    };
+                    format!(
+                        r#"{{"column":{},"score":{},"bound":"{bound}"}}"#,
+                        c.column, c.score
+                    )
+                })
+                .collect(),
+        );
+
+        format!(
+            r#"{{"depth":{},"nodes":{},"time_ms":{},"nps":{},"score":{},"plies_to_end":{},"pv":[{}],"scores":[{}]}}"#,
+            self.depth,
+            self.nodes,
+            self.elapsed.as_millis(),
+            self.nps(),
+            self.score,
+            plies_to_end,
+            principal_variation,
+            column_scores,
+        )
+    }
+}
+
+impl Display for SearchReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Depth {}, ", self.depth)?;
+        match self.plies_to_end() {
+            Some(plies) if self.score > 0 => write!(f, "wins in {plies}, ")?,
+            Some(plies) => write!(f, "loses in {plies}, ")?,
+            None => write!(f, "score {}, ", self.score)?,
+        }
+        writeln!(
+            f,
+            "{} nodes in {:.2}s ({} nodes/s).",
+            self.nodes,
+            self.elapsed.as_secs_f64(),
+            self.nps()
+        )?;
+
+        let principal_variation: Vec<_> = self
+            .principal_variation
+            .iter()
+            .map(|column| column.to_string())
+            .collect();
+        writeln!(f, "Best line: {}", principal_variation.join(" "))?;
+
+        let column_scores: Vec<_> = self
+            .column_scores
+            .iter()
+            .map(|c| match c.bound {
+                ScoreBound::Exact => format!("{}: {}", c.column, c.score),
+                ScoreBound::Upper => format!("{}: at most {}", c.column, c.score),
+            })
+            .collect();
+        write!(f, "Columns: {}", column_scores.join(", "))
+    }
+}
+
+/// Where the bot sends a report after each search. The bot plays its move whether or not the
+/// report could be written.
+pub trait ReportSink: Send {
+    fn report(&mut self, report: &SearchReport) -> std::io::Result<()>;
+}
+
+/// Any `FnMut(&SearchReport)` works as a sink, to collect reports or forward them elsewhere.
+impl<F> ReportSink for F
+where
+    F: FnMut(&SearchReport) + Send,
+{
+    fn report(&mut self, report: &SearchReport) -> std::io::Result<()> {
+        self(report);
+        Ok(())
+    }
+}
+
+/// Drops every report. This is what the bot uses unless told otherwise.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Silent;
+
+impl ReportSink for Silent {
+    fn report(&mut self, _: &SearchReport) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Writes each report as a few lines of text.
+#[derive(Debug)]
+pub struct HumanReadable<W> {
+    writer: W,
+}
+
+impl<W: Write + Send> HumanReadable<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+}
+
+impl<W: Write + Send> ReportSink for HumanReadable<W> {
+    fn report(&mut self, report: &SearchReport) -> std::io::Result<()> {
+        writeln!(self.writer, "{report}")
+    }
+}
+
+/// Writes each report as a line of JSON, for other tools to read.
+#[derive(Debug)]
+pub struct JsonLines<W> {
+    writer: W,
+}
+
+impl<W: Write + Send> JsonLines<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+}
+
+impl<W: Write + Send> ReportSink for JsonLines<W> {
+    fn report(&mut self, report: &SearchReport) -> std::io::Result<()> {
+        writeln!(self.writer, "{}", report.to_json())?;
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn column_score(column: usize, score: isize, bound: ScoreBound) -> ColumnScore {
+        ColumnScore {
+            column,
+            score,
+            bound,
+        }
+    }
+
+    fn report(score: isize) -> SearchReport {
+        SearchReport::new(
+            5,
+            1500,
+            Duration::from_millis(500),
+            score,
+            vec![3, 2, 3],
+            vec![
+                column_score(2, -40, ScoreBound::Upper),
+                column_score(3, score, ScoreBound::Exact),
+                column_score(4, -12, ScoreBound::Upper),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_accessors() {
+        let report = report(25);
+
+        assert_eq!(report.nps(), 3000);
+        assert_eq!(report.best_move(), Some(3));
+        assert_eq!(report.plies_to_end(), None);
+    }
+
+    #[test]
+    fn test_forced_result() {
+        assert_eq!(report(GameTree::WIN_SCORE - 7).plies_to_end(), Some(7));
+        assert_eq!(report(-GameTree::WIN_SCORE + 4).plies_to_end(), Some(4));
+    }
+
+    #[test]
+    fn test_json() {
+        assert_eq!(
+            report(25).to_json(),
+            r#"{"depth":5,"nodes":1500,"time_ms":500,"nps":3000,"score":25,"plies_to_end":null,"pv":[3,2,3],"scores":[{"column":2,"score":-40,"bound":"upper"},{"column":3,"score":25,"bound":"exact"},{"column":4,"score":-12,"bound":"upper"}]}"#
+        );
+    }
+
+    #[test]
+    fn test_human_readable() {
+        let mut output = vec![];
+
+        HumanReadable::new(&mut output)
+            .report(&report(GameTree::WIN_SCORE - 3))
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "Depth 5, wins in 3, 1500 nodes in 0.50s (3000 nodes/s).\n\
+             Best line: 3 2 3\n\
+             Columns: 2: at most -40, 3: 999997, 4: at most -12\n"
+        );
+    }
+
+    #[test]
+    fn test_json_lines() {
+        let mut output = vec![];
+        let mut sink = JsonLines::new(&mut output);
+
+        sink.report(&report(1)).unwrap();
+        sink.report(&report(2)).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines, [report(1).to_json(), report(2).to_json()]);
+    }
+}
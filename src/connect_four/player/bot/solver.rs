@@ -0,0 +1,666 @@
+use crate::connect_four::board::Board;
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum SolveError {
+    #[error("Only the standard 7x6 board with four in a row to win can be solved.")]
+    UnsupportedDimensions,
+    #[error("The game is already over.")]
+    GameOver,
+}
+
+/// How the game ends for the side to move when both sides play perfectly.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Outcome {
+    Win,
+    Draw,
+    Loss,
+}
+
+/// The value of a position under perfect play, from the point of view of the side to move.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Score {
+    value: i32,
+    plies: usize,
+    best_move: usize,
+}
+
+impl Score {
+    pub(super) fn new(value: i32, moves: usize, best_move: usize) -> Self {
+        Self {
+            value,
+            plies: Self::plies_left(value, moves),
+            best_move,
+        }
+    }
+
+    /// The score of winning with the next disc, in `column`.
+    pub(super) fn win_next(moves: usize, column: usize) -> Self {
+        Self::new(Position::win_now(moves), moves, column)
+    }
+
+    /// Turns a value back into plies, from the discs the winner ends with and the discs they
+    /// have now. The side to move has `moves / 2` of them and the opponent the rest.
+    fn plies_left(value: i32, moves: usize) -> usize {
+        let winner_discs = (22 - value.unsigned_abs()) as usize;
+
+        match value {
+            v if v > 0 => 2 * (winner_discs - moves / 2) - 1,
+            0 => CELLS - moves,
+            _ => 2 * (winner_discs - moves.div_ceil(2)),
+        }
+    }
+
+    /// 0 for a draw. Otherwise 22 less the number of discs the winner has down once they win,
+    /// positive when the side to move wins, so quicker wins are further from 0.
+    pub fn value(&self) -> i32 {
+        self.value
+    }
+
+    pub fn outcome(&self) -> Outcome {
+        match self.value {
+            v if v > 0 => Outcome::Win,
+            0 => Outcome::Draw,
+            _ => Outcome::Loss,
+        }
+    }
+
+    /// Discs still to be played until the game is won or lost, or until the board is full for
+    /// a draw.
+    pub fn plies(&self) -> usize {
+        self.plies
+    }
+
+    /// A column that keeps the score. Of several, the one nearest the centre.
+    pub fn best_move(&self) -> usize {
+        self.best_move
+    }
+}
+
+/// Exact solver for the standard board.
+///
+/// It runs a negamax over the bitboards with null window searches, which only ask whether the
+/// score is above a guess, and narrows the guess down to the exact score. Moves that hand the
+/// opponent a win are never searched, and the rest go from the centre out, best threat count
+/// first. Every bound found is kept in a table of its own that lasts between solves, shared
+/// by a position and its mirror image.
+pub struct Solver {
+    table: BoundTable,
+    nodes: u64,
+}
+
+impl Solver {
+    /// 64 MiB of bounds.
+    const DEFAULT_TABLE_CAPACITY: usize = 1 << 23;
+
+    pub fn new() -> Self {
+        Self::with_table_capacity(Self::DEFAULT_TABLE_CAPACITY)
+    }
+
+    pub fn with_table_capacity(capacity: usize) -> Self {
+        Self {
+            table: BoundTable::new(capacity),
+            nodes: 0,
+        }
+    }
+
+    /// Positions visited by the last solve.
+    pub fn nodes(&self) -> u64 {
+        self.nodes
+    }
+
+    pub fn solve(&mut self, board: &Board) -> Result<Score, SolveError> {
+        Self::check_dimensions(board)?;
+        if board.check_for_win().is_some() {
+            return Err(SolveError::GameOver);
+        }
+
+        let (current, mask) = board.bitboards();
+        let position = Position {
+            current,
+            mask,
+            moves: board.moves_played(),
+        };
+
+        self.nodes = 0;
+
+        let value = self.value(&position);
+        let best_move = self.best_move(&position, value);
+
+        Ok(Score::new(value, position.moves, best_move))
+    }
+
+    pub(super) fn check_dimensions(board: &Board) -> Result<(), SolveError> {
+        let dimensions = (board.width(), board.height(), board.connect_n());
+        if dimensions != (WIDTH, HEIGHT, 4) {
+            return Err(SolveError::UnsupportedDimensions);
+        }
+
+        Ok(())
+    }
+
+    /// The exact score, found by null window searches around a guess that moves towards it.
+    fn value(&mut self, position: &Position) -> i32 {
+        if position.can_win_next() {
+            return Position::win_now(position.moves);
+        }
+
+        let mut min = -(((CELLS - position.moves) / 2) as i32);
+        let mut max = ((CELLS + 1 - position.moves) / 2) as i32;
+
+        while min < max {
+            let mut guess = min + (max - min) / 2;
+
+            // Check small scores before large ones. They are far more common, and cheaper.
+            if guess <= 0 && min / 2 < guess {
+                guess = min / 2;
+            } else if guess >= 0 && max / 2 > guess {
+                guess = max / 2;
+            }
+
+            let score = self.negamax(position, guess, guess + 1);
+            if score <= guess {
+                max = score;
+            } else {
+                min = score;
+            }
+        }
+
+        min
+    }
+
+    /// The first move, from the centre out, whose score is `value`.
+    fn best_move(&mut self, position: &Position, value: i32) -> usize {
+        let possible = position.possible();
+        let mut fallback = None;
+
+        for column in CENTRE_FIRST {
+            let move_bit = possible & Position::column_mask(column);
+            if move_bit == 0 {
+                continue;
+            }
+            fallback.get_or_insert(column);
+
+            if position.winning_position() & move_bit != 0 {
+                return column;
+            }
+
+            let child = position.play(move_bit);
+            let score = if child.can_win_next() {
+                -Position::win_now(child.moves)
+            } else {
+                -self.negamax(&child, -value, -value + 1)
+            };
+
+            if score >= value {
+                return column;
+            }
+        }
+
+        // SAFETY: The game isn't over, so there is at least one move.
+        fallback.unwrap()
+    }
+
+    /// Score of `position` if it lies strictly between `alpha` and `beta`. Otherwise a bound
+    /// on the same side of the window as the score.
+    ///
+    /// The side to move must not be able to win straight away.
+    fn negamax(&mut self, position: &Position, mut alpha: i32, mut beta: i32) -> i32 {
+        self.nodes += 1;
+
+        let next = position.non_losing_moves();
+        if next == 0 {
+            // Every move lets the opponent win with their next disc.
+            return -(((CELLS - position.moves) / 2) as i32);
+        }
+        if position.moves >= CELLS - 2 {
+            // Neither side can win with the last two discs.
+            return 0;
+        }
+
+        // The opponent can't win straight away, and neither can the side to move.
+        let min = -(((CELLS - 2 - position.moves) / 2) as i32);
+        if alpha < min {
+            alpha = min;
+            if alpha >= beta {
+                return alpha;
+            }
+        }
+
+        let max = ((CELLS - 1 - position.moves) / 2) as i32;
+        if beta > max {
+            beta = max;
+            if alpha >= beta {
+                return beta;
+            }
+        }
+
+        let key = position.key();
+        match self.table.get(key) {
+            Some(Bound::Lower(score)) if score > alpha => {
+                alpha = score;
+                if alpha >= beta {
+                    return alpha;
+                }
+            }
+            Some(Bound::Upper(score)) if score < beta => {
+                beta = score;
+                if alpha >= beta {
+                    return beta;
+                }
+            }
+            _ => {}
+        }
+
+        for move_bit in position.sorted_moves(next) {
+            let score = -self.negamax(&position.play(move_bit), -beta, -alpha);
+
+            if score >= beta {
+                self.table.set(key, position.moves, Bound::Lower(score));
+                return score;
+            }
+            alpha = alpha.max(score);
+        }
+
+        self.table.set(key, position.moves, Bound::Upper(alpha));
+        alpha
+    }
+}
+
+impl Default for Solver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+const WIDTH: usize = 7;
+const HEIGHT: usize = 6;
+const CELLS: usize = WIDTH * HEIGHT;
+const CENTRE_FIRST: [usize; WIDTH] = [3, 2, 4, 1, 5, 0, 6];
+
+/// The bottom cell of every column.
+const BOTTOM: u64 = {
+    let mut bottom = 0;
+    let mut column = 0;
+    while column < WIDTH {
+        bottom |= 1 << (column * (HEIGHT + 1));
+        column += 1;
+    }
+    bottom
+};
+const BOARD: u64 = BOTTOM * ((1 << HEIGHT) - 1);
+
+/// A copy of a `Board`'s bitboards, without the bookkeeping a game needs, that is cheap to
+/// play moves on.
+#[derive(Debug, Copy, Clone)]
+struct Position {
+    /// Discs of the side to move.
+    current: u64,
+    mask: u64,
+    moves: usize,
+}
+
+impl Position {
+    fn win_now(moves: usize) -> i32 {
+        ((CELLS + 1 - moves) / 2) as i32
+    }
+
+    fn column_mask(column: usize) -> u64 {
+        ((1 << HEIGHT) - 1) << (column * (HEIGHT + 1))
+    }
+
+    /// The lowest empty cell of every column that isn't full.
+    fn possible(&self) -> u64 {
+        (self.mask + BOTTOM) & BOARD
+    }
+
+    fn play(&self, move_bit: u64) -> Self {
+        Self {
+            current: self.current ^ self.mask,
+            mask: self.mask | move_bit,
+            moves: self.moves + 1,
+        }
+    }
+
+    fn can_win_next(&self) -> bool {
+        self.winning_position() & self.possible() != 0
+    }
+
+    /// Empty cells that would complete a line for the side to move.
+    fn winning_position(&self) -> u64 {
+        Self::threats(self.current, self.mask)
+    }
+
+    fn opponent_winning_position(&self) -> u64 {
+        Self::threats(self.current ^ self.mask, self.mask)
+    }
+
+    /// Moves that don't give the opponent a win on their next disc, which is none if they have
+    /// two places to win or a move would have to go under where they win.
+    fn non_losing_moves(&self) -> u64 {
+        let mut possible = self.possible();
+        let opponent_wins = self.opponent_winning_position();
+        let forced = possible & opponent_wins;
+
+        if forced != 0 {
+            if forced & (forced - 1) != 0 {
+                return 0;
+            }
+            possible = forced;
+        }
+
+        possible & !(opponent_wins >> 1)
+    }
+
+    /// The single cell moves in `moves`, from the centre out and then by how many threats they
+    /// leave the side to move with, most first.
+    fn sorted_moves(&self, moves: u64) -> impl Iterator<Item = u64> {
+        let mut sorted = [(0, 0); WIDTH];
+        let mut len = 0;
+
+        for column in CENTRE_FIRST {
+            let move_bit = moves & Self::column_mask(column);
+            if move_bit == 0 {
+                continue;
+            }
+
+            let threats = Self::threats(self.current | move_bit, self.mask).count_ones();
+
+            // Insertion sort, keeping the centre first order among equal threat counts.
+            let mut i = len;
+            while i > 0 && sorted[i - 1].0 < threats {
+                sorted[i] = sorted[i - 1];
+                i -= 1;
+            }
+            sorted[i] = (threats, move_bit);
+            len += 1;
+        }
+
+        sorted.into_iter().take(len).map(|(_, move_bit)| move_bit)
+    }
+
+    /// Unique for every position up to mirror images, which share a key and a score. It fits
+    /// in the 49 bits of the board with its extra row.
+    fn key(&self) -> u64 {
+        let key = self.current + self.mask;
+        key.min(Self::mirror(key))
+    }
+
+    /// The same cells with the columns in the opposite order.
+    fn mirror(cells: u64) -> u64 {
+        let column = (1 << (HEIGHT + 1)) - 1;
+        let mut mirrored = 0;
+
+        for i in 0..WIDTH {
+            let bits = (cells >> (i * (HEIGHT + 1))) & column;
+            mirrored |= bits << ((WIDTH - 1 - i) * (HEIGHT + 1));
+        }
+
+        mirrored
+    }
+
+    /// Empty cells where a disc would give `discs` four in a row.
+    fn threats(discs: u64, mask: u64) -> u64 {
+        // Vertical.
+        let mut threats = (discs << 1) & (discs << 2) & (discs << 3);
+
+        for shift in [HEIGHT + 1, HEIGHT, HEIGHT + 2] {
+            // Three along the line on one side of the cell, or two on one side and one on the
+            // other.
+            let pair = (discs << shift) & (discs << (2 * shift));
+            threats |= pair & (discs << (3 * shift));
+            threats |= pair & (discs >> shift);
+
+            let pair = (discs >> shift) & (discs >> (2 * shift));
+            threats |= pair & (discs << shift);
+            threats |= pair & (discs >> (3 * shift));
+        }
+
+        threats & (BOARD ^ mask)
+    }
+}
+
+/// What a search showed about a position's score.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Bound {
+    Lower(i32),
+    Upper(i32),
+}
+
+/// The solver's transposition table.
+///
+/// Each slot holds a whole position key, the number of discs played and a byte for the bound,
+/// so a slot never answers for another position. A key can go in either of a pair of slots.
+/// When both are taken, the bound of the position with fewer discs is kept, as it took longer
+/// to find.
+struct BoundTable {
+    slots: Vec<u64>,
+}
+
+impl BoundTable {
+    /// Set in the bound's byte for a lower bound.
+    const LOWER: u64 = 0x80;
+    /// Added to scores so they are never 0, which marks an empty slot.
+    const OFFSET: i32 = 64;
+    const KEY_SHIFT: u32 = 14;
+
+    fn new(capacity: usize) -> Self {
+        Self {
+            slots: vec![0; capacity.max(2).next_multiple_of(2)],
+        }
+    }
+
+    fn get(&self, key: u64) -> Option<Bound> {
+        let index = self.index(key);
+        let slot = self.slots[index..index + 2]
+            .iter()
+            .find(|slot| **slot != 0 && **slot >> Self::KEY_SHIFT == key)?;
+
+        let score = (slot & 0x7F) as i32 - Self::OFFSET;
+        Some(match slot & Self::LOWER {
+            0 => Bound::Upper(score),
+            _ => Bound::Lower(score),
+        })
+    }
+
+    fn set(&mut self, key: u64, moves: usize, bound: Bound) {
+        let byte = match bound {
+            Bound::Lower(score) => Self::LOWER | (score + Self::OFFSET) as u64,
+            Bound::Upper(score) => (score + Self::OFFSET) as u64,
+        };
+        let new = key << Self::KEY_SHIFT | (moves as u64) << 8 | byte;
+
+        let index = self.index(key);
+        let moves_of = |slot: u64| (slot >> 8) & 0x3F;
+        let [first, second] = [self.slots[index], self.slots[index + 1]];
+
+        let i = if first == 0 || first >> Self::KEY_SHIFT == key {
+            index
+        } else if second == 0 || second >> Self::KEY_SHIFT == key {
+            index + 1
+        } else if moves_of(first) >= moves_of(second) {
+            index
+        } else {
+            index + 1
+        };
+        self.slots[i] = new;
+    }
+
+    /// The first of the pair of slots for `key`. The keys of nearby positions differ in few
+    /// bits, so they are mixed first.
+    fn index(&self, key: u64) -> usize {
+        let mut hash = key;
+        hash = (hash ^ (hash >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        hash = (hash ^ (hash >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        hash ^= hash >> 31;
+
+        2 * (hash % (self.slots.len() as u64 / 2)) as usize
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::connect_four::board::GameResult;
+
+    use rand::{seq::SliceRandom, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+    use rstest::rstest;
+
+    /// The value of `board` from every move to the end of the game with nothing pruned, on the
+    /// solver's scale. Only quick with a few empty cells left.
+    fn exhaustive_value(board: &mut Board) -> i32 {
+        let color = board.side_to_move();
+        let mut best = i32::MIN;
+
+        for column in board.list_valid_moves() {
+            let value = match board.apply_move(column, color).unwrap() {
+                Some(GameResult::Draw) => 0,
+                Some(_) => ((CELLS + 2 - board.moves_played()) / 2) as i32,
+                None => -exhaustive_value(board),
+            };
+            board.remove_move(column, color).unwrap();
+            best = best.max(value);
+        }
+
+        best
+    }
+
+    #[test]
+    fn test_matches_exhaustive_search() {
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        let mut solver = Solver::new();
+        let mut checked = 0;
+
+        while checked < 40 {
+            let mut board = Board::new();
+            let mut result = None;
+            while result.is_none() && board.moves_played() < CELLS - 10 {
+                let column = *board.list_valid_moves().choose(&mut rng).unwrap();
+                result = board.apply_move(column, board.side_to_move()).unwrap();
+            }
+            if result.is_some() {
+                continue;
+            }
+
+            let score = solver.solve(&board).unwrap();
+
+            assert_eq!(
+                score.value(),
+                exhaustive_value(&mut board),
+                "{}",
+                board.to_notation()
+            );
+            checked += 1;
+        }
+    }
+
+    /// Counting nodes rather than time keeps this steady on any machine. It takes about
+    /// 450,000.
+    #[test]
+    fn test_solves_middlegame_within_node_bound() {
+        let board = Board::from_moves("44433526").unwrap();
+        let mut solver = Solver::new();
+
+        let score = solver.solve(&board).unwrap();
+
+        assert_eq!(score.value(), 6);
+        assert!(solver.nodes() < 600_000, "{} nodes", solver.nodes());
+    }
+
+    /// The published values of the opening moves: only the centre wins for the first player,
+    /// the columns next to it draw and the rest lose. They are the values with the second
+    /// player to move, so the signs are flipped from how they are usually given. The mirror
+    /// images come for free from the table.
+    ///
+    /// This takes about eight minutes in a release build, so run it with
+    /// `cargo test --release -- --ignored`.
+    #[test]
+    #[ignore = "takes minutes"]
+    fn test_opening_moves() {
+        let mut solver = Solver::new();
+
+        for (moves, value) in [("4", -1), ("3", 0), ("2", 1), ("1", 2)] {
+            let board = Board::from_moves(moves).unwrap();
+            assert_eq!(solver.solve(&board).unwrap().value(), value, "{moves}");
+            assert_eq!(solver.solve(&board.mirrored()).unwrap().value(), value);
+        }
+    }
+
+    #[rstest]
+    #[case::win_now("112233", Outcome::Win, 1, 3)]
+    #[case::lose_next("33445", Outcome::Loss, 2, 3)]
+    #[case::win_soon("44444326555", Outcome::Win, 3, 2)]
+    #[case::draw("1576317533354222565122277737", Outcome::Draw, 14, 2)]
+    fn test_solve(
+        #[case] moves: &str,
+        #[case] outcome: Outcome,
+        #[case] plies: usize,
+        #[case] best_move: usize,
+    ) {
+        let board = Board::from_moves(moves).unwrap();
+        let score = Solver::new().solve(&board).unwrap();
+
+        assert_eq!(score.outcome(), outcome);
+        assert_eq!(score.plies(), plies);
+        assert_eq!(score.best_move(), best_move);
+    }
+
+    #[test]
+    fn test_best_move_keeps_score() {
+        let mut solver = Solver::new();
+        let board = Board::from_moves("44444326555").unwrap();
+
+        let score = solver.solve(&board).unwrap();
+
+        let mut child = board.clone();
+        let result = child
+            .apply_move(score.best_move(), board.side_to_move())
+            .unwrap();
+
+        match result {
+            Some(_) => assert_eq!(score.plies(), 1),
+            None => assert_eq!(solver.solve(&child).unwrap().value(), -score.value()),
+        }
+    }
+
+    #[test]
+    fn test_solve_errors() {
+        let mut solver = Solver::new();
+
+        assert_eq!(
+            solver.solve(&Board::with_dimensions(6, 5, 4).unwrap()),
+            Err(SolveError::UnsupportedDimensions)
+        );
+        assert_eq!(
+            solver.solve(&Board::from_moves("1212121").unwrap()),
+            Err(SolveError::GameOver)
+        );
+    }
+
+    #[test]
+    fn test_threats_match_board() {
+        let board = Board::from_moves("4453366217").unwrap();
+        let (current, mask) = board.bitboards();
+        let position = Position {
+            current,
+            mask,
+            moves: board.moves_played(),
+        };
+
+        let cells = |threats: u64| {
+            let mut cells: Vec<_> = (0..WIDTH)
+                .flat_map(|column| (0..HEIGHT).map(move |row| (row, column)))
+                .filter(|(row, column)| threats & (1 << (column * (HEIGHT + 1) + row)) != 0)
+                .collect();
+            cells.sort_by_key(|(row, column)| (*column, *row));
+            cells
+        };
+
+        let to_move = board.side_to_move();
+        assert_eq!(cells(position.winning_position()), board.threats(to_move));
+        assert_eq!(
+            cells(position.opponent_winning_position()),
+            board.threats(to_move.flip_into())
+        );
+    }
+}
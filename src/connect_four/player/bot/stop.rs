@@ -0,0 +1,49 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// Asks a running search to stop and play the best move it has so far.
+///
+/// Clones share the same flag, so one can be handed to a search while another is kept to stop
+/// it from a signal handler or another thread.
+#[derive(Debug, Clone, Default)]
+pub struct StopToken(Arc<AtomicBool>);
+
+impl StopToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn stop(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Let searches run again.
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_clones_share_the_flag() {
+        let token = StopToken::new();
+        let handle = token.clone();
+
+        assert!(!token.is_stopped());
+
+        handle.stop();
+        assert!(token.is_stopped());
+
+        token.reset();
+        assert!(!handle.is_stopped());
+    }
+}
@@ -0,0 +1,317 @@
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+
+use crate::connect_four::{board::Board, square::Square};
+
+/// How an entry's score relates to the true value of the position.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Bound {
+    /// The score is the value of the position.
+    Exact,
+    /// The search failed high, so the value is at least the score.
+    Lower,
+    /// The search failed low, so the value is at most the score.
+    Upper,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Entry {
+    pub key: u64,
+    /// Plies searched below the position.
+    pub depth: usize,
+    pub bound: Bound,
+    pub score: isize,
+    pub best_move: Option<usize>,
+    generation: u8,
+}
+
+impl Entry {
+    pub fn new(
+        key: u64,
+        depth: usize,
+        bound: Bound,
+        score: isize,
+        best_move: Option<usize>,
+    ) -> Self {
+        Self {
+            key,
+            depth,
+            bound,
+            score,
+            best_move,
+            generation: 0,
+        }
+    }
+}
+
+/// A fixed size, always-allocated cache of searched positions.
+///
+/// Each key maps to a single slot, so memory use never grows past the capacity given at
+/// construction. When two positions compete for a slot, entries from an older search are
+/// always replaced, and within the same search the deeper result is kept.
+///
+/// The table is shared between search threads without locks. An entry is packed into one word
+/// and stored next to its key xor that word, so an entry torn by two threads writing at once
+/// no longer matches its key and reads as missing.
+pub struct TranspositionTable {
+    entries: Vec<[AtomicU64; 2]>,
+    generation: AtomicU8,
+}
+
+impl TranspositionTable {
+    /// `capacity` is rounded up to the next power of two.
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1).next_power_of_two();
+
+        Self {
+            entries: (0..capacity)
+                .map(|_| [AtomicU64::new(0), AtomicU64::new(0)])
+                .collect(),
+            generation: AtomicU8::new(0),
+        }
+    }
+
+    /// Mixed into the key when Red is to move.
+    const RED_TO_MOVE: u64 = 0x8F3A_61C2_5D97_0B4E;
+
+    /// Set in every packed entry, so an empty slot never matches.
+    const OCCUPIED: u64 = 1 << 63;
+    const NO_MOVE: u64 = 0xFF;
+
+    /// Key for `board` with `color` to move, built on the board's canonical Zobrist key so a
+    /// position and its mirror image share an entry.
+    pub fn key(board: &Board, color: Square) -> u64 {
+        match color {
+            Square::Red => board.canonical_key() ^ Self::RED_TO_MOVE,
+            _ => board.canonical_key(),
+        }
+    }
+
+    /// Entries store moves for the canonical position. This maps a column of `board` to the
+    /// canonical position or back again.
+    pub fn orient(board: &Board, column: usize) -> usize {
+        if board.mirror_key() < board.key() {
+            board.mirror_column(column)
+        } else {
+            column
+        }
+    }
+
+    /// Mark every stored entry as coming from an older search.
+    pub fn new_search(&self) {
+        self.generation.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn probe(&self, key: u64) -> Option<Entry> {
+        let [checked_key, data] = &self.entries[self.index(key)];
+        let data = data.load(Ordering::Relaxed);
+        let checked_key = checked_key.load(Ordering::Relaxed);
+
+        (data & Self::OCCUPIED != 0 && checked_key ^ data == key).then(|| Self::unpack(key, data))
+    }
+
+    pub fn store(&self, mut entry: Entry) {
+        // The top bit of the packed entry marks it occupied, which leaves seven for this.
+        entry.generation = self.generation.load(Ordering::Relaxed) & 0x7F;
+
+        let replace = match self.probe_slot(entry.key) {
+            None => true,
+            Some(old) => {
+                old.key == entry.key
+                    || old.generation != entry.generation
+                    || old.depth <= entry.depth
+            }
+        };
+
+        if replace {
+            let data = Self::pack(&entry);
+            let [checked_key, slot] = &self.entries[self.index(entry.key)];
+            slot.store(data, Ordering::Relaxed);
+            checked_key.store(entry.key ^ data, Ordering::Relaxed);
+        }
+    }
+
+    /// Whatever entry is in the slot for `key`, whether or not it is for `key`.
+    fn probe_slot(&self, key: u64) -> Option<Entry> {
+        let [checked_key, data] = &self.entries[self.index(key)];
+        let data = data.load(Ordering::Relaxed);
+        let stored_key = checked_key.load(Ordering::Relaxed) ^ data;
+
+        (data & Self::OCCUPIED != 0).then(|| Self::unpack(stored_key, data))
+    }
+
+    /// The score in the low 32 bits, then a byte each for the depth, bound, best move and
+    /// generation, with the occupied bit on top.
+    fn pack(entry: &Entry) -> u64 {
+        let bound = match entry.bound {
+            Bound::Exact => 0,
+            Bound::Lower => 1,
+            Bound::Upper => 2,
+        };
+        let best_move = entry.best_move.map_or(Self::NO_MOVE, |m| m as u64);
+
+        (entry.score as i32 as u32 as u64)
+            | (entry.depth.min(u8::MAX as usize) as u64) << 32
+            | bound << 40
+            | best_move << 48
+            | (entry.generation as u64) << 56
+            | Self::OCCUPIED
+    }
+
+    fn unpack(key: u64, data: u64) -> Entry {
+        let bound = match (data >> 40) & 0xFF {
+            0 => Bound::Exact,
+            1 => Bound::Lower,
+            _ => Bound::Upper,
+        };
+        let best_move = match (data >> 48) & 0xFF {
+            Self::NO_MOVE => None,
+            m => Some(m as usize),
+        };
+
+        Entry {
+            key,
+            depth: ((data >> 32) & 0xFF) as usize,
+            bound,
+            score: data as u32 as i32 as isize,
+            best_move,
+            generation: ((data >> 56) & 0x7F) as u8,
+        }
+    }
+
+    fn index(&self, key: u64) -> usize {
+        // The capacity is a power of two, so this is a cheap modulo.
+        (key as usize) & (self.entries.len() - 1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_store_and_probe() {
+        let table = TranspositionTable::new(16);
+        let entry = Entry::new(3, 4, Bound::Exact, 10, Some(2));
+
+        table.store(entry);
+
+        assert_eq!(table.probe(3), Some(entry));
+        assert_eq!(table.probe(4), None);
+        // Same slot, different key.
+        assert_eq!(table.probe(3 + 16), None);
+    }
+
+    #[test]
+    fn test_capacity_is_bounded() {
+        let table = TranspositionTable::new(100);
+
+        assert_eq!(table.entries.len(), 128);
+
+        for key in 0..10_000 {
+            table.store(Entry::new(key, 1, Bound::Lower, 0, None));
+        }
+
+        let stored = (0..10_000)
+            .filter(|key| table.probe(*key).is_some())
+            .count();
+        assert_eq!(stored, 128);
+    }
+
+    #[test]
+    fn test_deeper_entry_is_kept() {
+        let table = TranspositionTable::new(16);
+
+        table.store(Entry::new(1, 5, Bound::Exact, 10, Some(2)));
+        table.store(Entry::new(17, 2, Bound::Exact, 20, Some(3)));
+
+        assert!(table.probe(1).is_some());
+        assert!(table.probe(17).is_none());
+
+        table.store(Entry::new(17, 6, Bound::Upper, 20, Some(3)));
+
+        assert!(table.probe(1).is_none());
+        assert_eq!(table.probe(17).unwrap().bound, Bound::Upper);
+    }
+
+    #[test]
+    fn test_older_search_is_replaced() {
+        let table = TranspositionTable::new(16);
+
+        table.store(Entry::new(1, 5, Bound::Exact, 10, Some(2)));
+        table.new_search();
+        table.store(Entry::new(17, 2, Bound::Exact, 20, Some(3)));
+
+        assert!(table.probe(1).is_none());
+        assert!(table.probe(17).is_some());
+    }
+
+    #[test]
+    fn test_same_position_is_updated() {
+        let table = TranspositionTable::new(16);
+
+        table.store(Entry::new(1, 5, Bound::Exact, 10, Some(2)));
+        table.store(Entry::new(1, 1, Bound::Lower, 30, Some(4)));
+
+        let entry = table.probe(1).unwrap();
+        assert_eq!(entry.depth, 1);
+        assert_eq!(entry.best_move, Some(4));
+    }
+
+    #[test]
+    fn test_key_shared_with_mirror_image() {
+        let board = Board::from_moves("1123").unwrap();
+        let mirrored = board.mirrored();
+
+        assert_eq!(
+            TranspositionTable::key(&board, Square::Yellow),
+            TranspositionTable::key(&mirrored, Square::Yellow)
+        );
+
+        // A move stored from one side comes back out mirrored on the other.
+        let stored = TranspositionTable::orient(&board, 1);
+        assert_eq!(TranspositionTable::orient(&mirrored, stored), 5);
+        assert_eq!(TranspositionTable::orient(&board, stored), 1);
+    }
+
+    #[test]
+    fn test_key_includes_color() {
+        let board = Board::new();
+
+        assert_ne!(
+            TranspositionTable::key(&board, Square::Yellow),
+            TranspositionTable::key(&board, Square::Red)
+        );
+    }
+
+    #[test]
+    fn test_shared_between_threads() {
+        let table = TranspositionTable::new(64);
+
+        // Every thread writes its own version of each key. However the writes interleave, a hit
+        // must be one whole entry for the key probed.
+        std::thread::scope(|scope| {
+            for thread in 0..4 {
+                let table = &table;
+                scope.spawn(move || {
+                    for round in 0..1000 {
+                        let key = round % 256;
+                        let score = (key * 4 + thread) as isize;
+                        table.store(Entry::new(
+                            key,
+                            1,
+                            Bound::Exact,
+                            score,
+                            Some(thread as usize),
+                        ));
+
+                        if let Some(entry) = table.probe(key) {
+                            assert_eq!(entry.key, key);
+                            assert_eq!(entry.score / 4, key as isize);
+                            assert_eq!(entry.best_move, Some((entry.score % 4) as usize));
+                        }
+                    }
+                });
+            }
+        });
+    }
+}
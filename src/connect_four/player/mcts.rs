@@ -0,0 +1,560 @@
+use std::time::{Duration, Instant};
+
+use crate::connect_four::{
+    board::{Board, GameResult},
+    player::Player,
+    square::Square,
+};
+
+use anyhow::Result;
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// How the rest of the game is played out from a new node.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum Playout {
+    /// Uniformly random moves.
+    Random,
+    /// Random moves, except that a side takes a win when it has one, blocks the opponent's, and
+    /// doesn't drop a disc right under a cell the opponent wins on.
+    #[default]
+    Heuristic,
+}
+
+/// How long `Mcts` thinks and how it balances trying new moves against the best ones so far.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MctsConfig {
+    /// Only set by `with_iterations`, so a move time alone doesn't get the default as well.
+    iterations: Option<u64>,
+    move_time: Option<Duration>,
+    exploration: f64,
+    playout: Playout,
+}
+
+impl MctsConfig {
+    const DEFAULT_ITERATIONS: u64 = 20_000;
+
+    pub fn new() -> Self {
+        Self {
+            iterations: None,
+            move_time: None,
+            exploration: std::f64::consts::SQRT_2,
+            playout: Playout::default(),
+        }
+    }
+
+    /// Stop after this many playouts. Without a move time, the default is 20,000.
+    pub fn with_iterations(mut self, iterations: u64) -> Self {
+        self.iterations = Some(iterations);
+        self
+    }
+
+    /// Stop once this much time has passed. Without an iteration count as well, this is the
+    /// only limit.
+    pub fn with_move_time(mut self, move_time: Duration) -> Self {
+        self.move_time = Some(move_time);
+        self
+    }
+
+    /// The UCT exploration constant. Larger values spread playouts over more moves.
+    pub fn with_exploration(mut self, exploration: f64) -> Self {
+        self.exploration = exploration;
+        self
+    }
+
+    pub fn with_playout(mut self, playout: Playout) -> Self {
+        self.playout = playout;
+        self
+    }
+
+    /// The playout limit in effect: the one given, or the default when there is no move time.
+    pub fn iterations(&self) -> Option<u64> {
+        match (self.iterations, self.move_time) {
+            (None, None) => Some(Self::DEFAULT_ITERATIONS),
+            (iterations, _) => iterations,
+        }
+    }
+
+    pub fn move_time(&self) -> Option<Duration> {
+        self.move_time
+    }
+
+    pub fn exploration(&self) -> f64 {
+        self.exploration
+    }
+
+    pub fn playout(&self) -> Playout {
+        self.playout
+    }
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Monte Carlo tree search with UCT selection.
+///
+/// Each iteration walks down the tree to the child with the best upper confidence bound, adds
+/// one untried move, plays the game out from it and counts the result back up the path. The
+/// move played is the most visited. The part of the tree below the position that comes up
+/// next is kept for the next move.
+pub struct Mcts {
+    config: MctsConfig,
+    tree: Option<Tree>,
+    iterations: u64,
+    seed: u64,
+    rng: ChaCha8Rng,
+}
+
+impl Player for Mcts {
+    type MoveData = usize;
+
+    fn is_human(&self) -> bool {
+        false
+    }
+
+    fn get_move(&mut self, current_board: &Board) -> Result<Self::MoveData> {
+        let mut tree = self
+            .tree
+            .take()
+            .and_then(|tree| tree.reuse(current_board))
+            .unwrap_or_else(|| Tree::new(current_board.clone()));
+
+        let deadline = self.config.move_time.map(|time| Instant::now() + time);
+        self.iterations = 0;
+
+        loop {
+            let out_of_iterations = self
+                .config
+                .iterations()
+                .is_some_and(|iterations| self.iterations >= iterations);
+            let out_of_time = deadline.is_some_and(|deadline| Instant::now() >= deadline);
+
+            // Always run at least one iteration, so the root has a child to pick.
+            if self.iterations > 0 && (out_of_iterations || out_of_time) {
+                break;
+            }
+
+            tree.iterate(&self.config, &mut self.rng);
+            self.iterations += 1;
+        }
+
+        // SAFETY: A user won't be asked to pick a move if there is a draw.
+        let best_move = tree.best_move().unwrap();
+        self.tree = Some(tree);
+
+        Ok(best_move)
+    }
+
+    fn seed(&self) -> Option<u64> {
+        Some(self.seed)
+    }
+}
+
+impl Mcts {
+    pub fn new() -> Self {
+        Self::with_config(MctsConfig::default())
+    }
+
+    pub fn with_config(config: MctsConfig) -> Self {
+        let seed = rand::random();
+
+        Self {
+            config,
+            tree: None,
+            iterations: 0,
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+
+    /// Play out games from `seed`, so the same moves come back every time. That only holds
+    /// with an iteration budget: with a move time, how many playouts fit depends on the machine.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self.rng = ChaCha8Rng::seed_from_u64(seed);
+        self
+    }
+
+    pub fn config(&self) -> &MctsConfig {
+        &self.config
+    }
+
+    /// Playouts run for the last move.
+    pub fn iterations(&self) -> u64 {
+        self.iterations
+    }
+}
+
+impl Default for Mcts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    board: Board,
+    player_move: usize,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried: Vec<usize>,
+    visits: u64,
+    /// Playouts won by the player who moved into this node, with draws as half a win.
+    wins: f64,
+}
+
+impl Node {
+    fn new(board: Board, player_move: usize, parent: Option<usize>) -> Self {
+        let untried = match board.check_for_win() {
+            Some(_) => vec![],
+            None => board.list_valid_moves(),
+        };
+
+        Self {
+            board,
+            player_move,
+            parent,
+            children: vec![],
+            untried,
+            visits: 0,
+            wins: 0.0,
+        }
+    }
+}
+
+/// The nodes live in one `Vec` and point at each other by index. The root is the first.
+#[derive(Debug, Clone)]
+struct Tree {
+    nodes: Vec<Node>,
+}
+
+impl Tree {
+    fn new(board: Board) -> Self {
+        Self {
+            nodes: vec![Node::new(board, 0, None)],
+        }
+    }
+
+    /// The subtree for `board`, if it is the root or comes up within two moves of it.
+    fn reuse(self, board: &Board) -> Option<Self> {
+        let root = &self.nodes[0];
+        if root.board == *board {
+            return Some(self);
+        }
+
+        let grandchildren = root
+            .children
+            .iter()
+            .flat_map(|child| self.nodes[*child].children.iter());
+        let (new_root, _) = root
+            .children
+            .iter()
+            .chain(grandchildren)
+            .map(|index| (*index, &self.nodes[*index]))
+            .find(|(_, node)| node.board == *board)?;
+
+        Some(self.subtree(new_root))
+    }
+
+    /// A copy of the tree below `new_root`, with it as the root.
+    fn subtree(&self, new_root: usize) -> Self {
+        let mut nodes = vec![];
+        let mut stack = vec![(new_root, None)];
+
+        while let Some((old, parent)) = stack.pop() {
+            let index = nodes.len();
+            let mut node = self.nodes[old].clone();
+            node.parent = parent;
+            node.children.clear();
+            nodes.push(node);
+
+            if let Some(parent) = parent {
+                let parent: &mut Node = &mut nodes[parent];
+                parent.children.push(index);
+            }
+
+            // Reversed, so children keep their order once popped.
+            for child in self.nodes[old].children.iter().rev() {
+                stack.push((*child, Some(index)));
+            }
+        }
+
+        Self { nodes }
+    }
+
+    fn iterate(&mut self, config: &MctsConfig, rng: &mut impl Rng) {
+        let mut node = 0;
+
+        // Selection.
+        while self.nodes[node].untried.is_empty() && !self.nodes[node].children.is_empty() {
+            node = self.select_child(node, config.exploration);
+        }
+
+        // Expansion.
+        if !self.nodes[node].untried.is_empty() {
+            let untried = &mut self.nodes[node].untried;
+            let column = untried.swap_remove(rng.gen_range(0..untried.len()));
+
+            let mut board = self.nodes[node].board.clone();
+            // SAFETY: Untried moves come from the valid moves.
+            board.apply_move(column, board.side_to_move()).unwrap();
+
+            let child = self.nodes.len();
+            self.nodes.push(Node::new(board, column, Some(node)));
+            self.nodes[node].children.push(child);
+            node = child;
+        }
+
+        // Simulation.
+        let winner = Self::playout(&self.nodes[node].board, config.playout, rng);
+
+        // Backpropagation.
+        let mut next = Some(node);
+        while let Some(index) = next {
+            let node = &mut self.nodes[index];
+            let mover = node.board.side_to_move().flip_into();
+
+            node.visits += 1;
+            node.wins += match winner {
+                Some(winner) if winner == mover => 1.0,
+                Some(_) => 0.0,
+                None => 0.5,
+            };
+
+            next = node.parent;
+        }
+    }
+
+    fn select_child(&self, node: usize, exploration: f64) -> usize {
+        let log_visits = (self.nodes[node].visits as f64).ln();
+
+        let uct = |child: &usize| {
+            let child = &self.nodes[*child];
+            let visits = child.visits as f64;
+
+            child.wins / visits + exploration * (log_visits / visits).sqrt()
+        };
+
+        // SAFETY: Only called on nodes with children, which have all been visited.
+        *self.nodes[node]
+            .children
+            .iter()
+            .max_by(|a, b| uct(a).total_cmp(&uct(b)))
+            .unwrap()
+    }
+
+    /// Play `board` out to the end and return the winner, if there is one.
+    fn playout(board: &Board, playout: Playout, rng: &mut impl Rng) -> Option<Square> {
+        let mut board = board.clone();
+
+        loop {
+            if let Some(result) = board.check_for_win() {
+                return match result {
+                    GameResult::Win(winner, _) => Some(winner),
+                    GameResult::Draw => None,
+                };
+            }
+
+            let color = board.side_to_move();
+            let column = match playout {
+                Playout::Random => Self::random_move(&board.list_valid_moves(), rng),
+                Playout::Heuristic => Self::heuristic_move(&board, color, rng),
+            };
+
+            // SAFETY: The column comes from the valid moves.
+            board.apply_move(column, color).unwrap();
+        }
+    }
+
+    fn heuristic_move(board: &Board, color: Square, rng: &mut impl Rng) -> usize {
+        if let Some(column) = board.winning_moves(color).first() {
+            return *column;
+        }
+
+        let blocks = board.moves_that_block(color);
+        if !blocks.is_empty() {
+            return Self::random_move(&blocks, rng);
+        }
+
+        let losing = board.moves_that_lose_immediately(color);
+        let safe: Vec<_> = board
+            .list_valid_moves()
+            .into_iter()
+            .filter(|column| !losing.contains(column))
+            .collect();
+
+        match safe.is_empty() {
+            true => Self::random_move(&board.list_valid_moves(), rng),
+            false => Self::random_move(&safe, rng),
+        }
+    }
+
+    fn random_move(moves: &[usize], rng: &mut impl Rng) -> usize {
+        // SAFETY: Only called with the moves of an unfinished game.
+        *moves.choose(rng).unwrap()
+    }
+
+    /// The root's most visited move.
+    fn best_move(&self) -> Option<usize> {
+        self.nodes[0]
+            .children
+            .iter()
+            .map(|child| &self.nodes[*child])
+            .max_by_key(|child| child.visits)
+            .map(|child| child.player_move)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rstest::rstest;
+
+    #[rstest]
+    #[case::random(Playout::Random)]
+    #[case::heuristic(Playout::Heuristic)]
+    fn test_takes_immediate_win(#[case] playout: Playout) {
+        let board = Board::new_from_str_vec(&[
+            "_______", "_______", "_______", "R______", "R______", "RYYY___",
+        ]);
+        let mut mcts = Mcts::with_config(MctsConfig::new().with_playout(playout)).with_seed(7);
+
+        assert_eq!(mcts.get_move(&board).unwrap(), 4);
+    }
+
+    #[rstest]
+    #[case::random(Playout::Random)]
+    #[case::heuristic(Playout::Heuristic)]
+    fn test_blocks_immediate_loss(#[case] playout: Playout) {
+        let board = Board::new_from_str_vec(&[
+            "_______", "_______", "_______", "____Y__", "____Y__", "R_R_Y__",
+        ]);
+        let mut mcts = Mcts::with_config(MctsConfig::new().with_playout(playout)).with_seed(7);
+
+        assert_eq!(mcts.get_move(&board).unwrap(), 4);
+    }
+
+    #[test]
+    fn test_same_seed_same_search() {
+        let board = Board::from_moves("4435").unwrap();
+        let config = MctsConfig::new().with_iterations(300);
+        let mut first = Mcts::with_config(config.clone()).with_seed(11);
+        let mut second = Mcts::with_config(config).with_seed(11);
+
+        assert_eq!(
+            first.get_move(&board).unwrap(),
+            second.get_move(&board).unwrap()
+        );
+
+        let visits = |mcts: &Mcts| {
+            let tree = mcts.tree.as_ref().unwrap();
+            tree.nodes
+                .iter()
+                .map(|node| node.visits)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(visits(&first), visits(&second));
+        assert_eq!(first.seed(), Some(11));
+    }
+
+    #[test]
+    fn test_iteration_budget() {
+        let mut mcts = Mcts::with_config(MctsConfig::new().with_iterations(50));
+
+        mcts.get_move(&Board::new()).unwrap();
+
+        assert_eq!(mcts.iterations(), 50);
+        assert_eq!(mcts.tree.as_ref().unwrap().nodes[0].visits, 50);
+    }
+
+    #[test]
+    fn test_move_time() {
+        let config = MctsConfig::new().with_move_time(Duration::from_millis(100));
+        let mut mcts = Mcts::with_config(config);
+
+        let start = Instant::now();
+        mcts.get_move(&Board::new()).unwrap();
+
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(mcts.iterations() > 0);
+        assert_eq!(mcts.config().iterations(), None);
+    }
+
+    #[rstest]
+    #[case::default(MctsConfig::new(), Some(20_000))]
+    #[case::move_time(MctsConfig::new().with_move_time(Duration::from_secs(1)), None)]
+    #[case::both(
+        MctsConfig::new().with_iterations(20_000).with_move_time(Duration::from_secs(1)),
+        Some(20_000)
+    )]
+    #[case::both_reversed(
+        MctsConfig::new().with_move_time(Duration::from_secs(1)).with_iterations(500),
+        Some(500)
+    )]
+    fn test_iteration_limit(#[case] config: MctsConfig, #[case] expected: Option<u64>) {
+        assert_eq!(config.iterations(), expected);
+    }
+
+    #[test]
+    fn test_tree_is_reused() {
+        let mut mcts = Mcts::with_config(MctsConfig::new().with_iterations(2000));
+        let mut board = Board::from_moves("4").unwrap();
+
+        let reply = mcts.get_move(&board).unwrap();
+        board.apply_move(reply, Square::Red).unwrap();
+        board.apply_move(3, Square::Yellow).unwrap();
+
+        let tree = mcts.tree.clone().unwrap().reuse(&board).unwrap();
+        let root = &tree.nodes[0];
+
+        assert_eq!(root.board, board);
+        assert_eq!(root.parent, None);
+        assert!(root.visits > 0);
+        assert_eq!(
+            root.visits,
+            root.children
+                .iter()
+                .map(|child| tree.nodes[*child].visits)
+                .sum::<u64>()
+                + 1
+        );
+    }
+
+    #[test]
+    fn test_unrelated_position_starts_over() {
+        let mut mcts = Mcts::with_config(MctsConfig::new().with_iterations(100));
+
+        mcts.get_move(&Board::from_moves("4").unwrap()).unwrap();
+        let tree = mcts.tree.clone().unwrap();
+
+        assert!(tree.reuse(&Board::from_moves("1234").unwrap()).is_none());
+    }
+
+    #[rstest]
+    #[case::greedy(0.0, 4)]
+    #[case::exploring(2.0, 3)]
+    fn test_exploration(#[case] exploration: f64, #[case] expected: usize) {
+        let mut tree = Tree::new(Board::new());
+        for (column, visits, wins) in [(4, 9, 6.0), (3, 1, 0.0)] {
+            let mut child = Node::new(
+                Board::from_moves(&(column + 1).to_string()).unwrap(),
+                column,
+                Some(0),
+            );
+            child.visits = visits;
+            child.wins = wins;
+            let index = tree.nodes.len();
+            tree.nodes.push(child);
+            tree.nodes[0].children.push(index);
+        }
+        tree.nodes[0].visits = 10;
+
+        let child = tree.select_child(0, exploration);
+
+        assert_eq!(tree.nodes[child].player_move, expected);
+    }
+}
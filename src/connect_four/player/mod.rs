@@ -4,10 +4,16 @@ use anyhow::Result;
 
 mod bot;
 mod human;
+mod mcts;
 mod random;
 
-pub use bot::Bot;
+pub use bot::{
+    BookError, Bot, BotConfig, ColumnScore, Difficulty, Evaluator, HumanReadable, JsonLines,
+    OpeningBook, Outcome, ParseDifficultyError, ReportSink, Score, ScoreBound, SearchReport,
+    Silent, SolveError, Solver, StopToken, ThreatEvaluator, WindowEvaluator,
+};
 pub use human::Human;
+pub use mcts::{Mcts, MctsConfig, Playout};
 pub use random::Random;
 
 pub trait Player {
@@ -15,4 +21,17 @@ pub trait Player {
 
     fn is_human(&self) -> bool;
     fn get_move(&mut self, current_board: &Board) -> Result<Self::MoveData>;
+
+    /// Called on both players after every move, with the board once `column` has been played.
+    /// Players that think on the opponent's time use it to start and stop.
+    fn notify_move(&mut self, board: &Board, column: usize) {
+        let _ = (board, column);
+    }
+
+    /// The seed behind the player's random choices, for players that make any. Giving a new
+    /// player the same seed replays its moves. They draw from ChaCha8, which gives the same
+    /// numbers for a seed on every platform and `rand` version.
+    fn seed(&self) -> Option<u64> {
+        None
+    }
 }
@@ -1,9 +1,13 @@
 use crate::connect_four::{board::Board, player::Player};
 
 use anyhow::Result;
-use rand::{seq::SliceRandom, thread_rng};
+use rand::{seq::SliceRandom, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 
-pub struct Random {}
+pub struct Random {
+    seed: u64,
+    rng: ChaCha8Rng,
+}
 
 impl Player for Random {
     type MoveData = usize;
@@ -13,12 +17,73 @@ impl Player for Random {
     }
 
     fn get_move(&mut self, current_board: &Board) -> Result<Self::MoveData> {
-        let mut rng = thread_rng();
         let moves = current_board.list_valid_moves();
 
         // SAFETY: A user won't be asked to pick a move if there is a draw.
-        let player_move: usize = *moves.choose(&mut rng).unwrap();
+        let player_move: usize = *moves.choose(&mut self.rng).unwrap();
 
         Ok(player_move)
     }
+
+    fn seed(&self) -> Option<u64> {
+        Some(self.seed)
+    }
+}
+
+impl Random {
+    /// A player with a seed of its own, which the game record keeps.
+    pub fn new() -> Self {
+        Self::with_seed(rand::random())
+    }
+
+    /// A player that makes the same moves every time it is given `seed`.
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Default for Random {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn moves(player: &mut Random) -> Vec<usize> {
+        let board = Board::new();
+
+        (0..20).map(|_| player.get_move(&board).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_same_seed_same_moves() {
+        let mut first = Random::with_seed(7);
+        let mut second = Random::with_seed(7);
+
+        assert_eq!(moves(&mut first), moves(&mut second));
+        assert_eq!(first.seed(), Some(7));
+    }
+
+    /// Recorded games rely on a seed always giving these moves, whatever the platform.
+    #[test]
+    fn test_seed_gives_fixed_moves() {
+        assert_eq!(
+            moves(&mut Random::with_seed(7)),
+            [1, 1, 1, 0, 5, 0, 4, 5, 2, 1, 0, 1, 2, 2, 3, 3, 1, 5, 2, 3]
+        );
+    }
+
+    #[test]
+    fn test_different_seeds() {
+        assert_ne!(
+            moves(&mut Random::with_seed(1)),
+            moves(&mut Random::with_seed(2))
+        );
+    }
 }
@@ -25,6 +25,21 @@ impl Square {
     }
 }
 
+impl TryFrom<char> for Square {
+    type Error = char;
+
+    /// The inverse of `Display`: `'Y'`, `'R'` or `'_'`. The unknown character is handed back
+    /// as the error.
+    fn try_from(letter: char) -> Result<Self, Self::Error> {
+        match letter {
+            'Y' => Ok(Self::Yellow),
+            'R' => Ok(Self::Red),
+            '_' => Ok(Self::Empty),
+            _ => Err(letter),
+        }
+    }
+}
+
 impl Display for Square {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
         let letter_repr = match self {
@@ -63,4 +78,19 @@ mod test {
         given.flip();
         assert_eq!(given, expected);
     }
+
+    #[rstest]
+    #[case::red(Square::Red)]
+    #[case::yellow(Square::Yellow)]
+    #[case::empty(Square::Empty)]
+    fn test_try_from_display(#[case] square: Square) {
+        let letter = square.to_string().chars().next().unwrap();
+
+        assert_eq!(Square::try_from(letter), Ok(square));
+    }
+
+    #[test]
+    fn test_try_from_fail() {
+        assert_eq!(Square::try_from('y'), Err('y'));
+    }
 }
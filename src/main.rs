@@ -1,14 +1,129 @@
-use board_games::connect_four::{game::Game, player, square::Square};
+use board_games::connect_four::{
+    board::Board,
+    game::Game,
+    player::{
+        self, BookError, BotConfig, Difficulty, HumanReadable, JsonLines, OpeningBook, StopToken,
+    },
+    square::Square,
+};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use std::time::Duration;
 
+const BOOK_PATH: &str = "opening.book";
+
+/// Usage: `board_games [OPTIONS] [difficulty] [WIDTHxHEIGHTxCONNECT | MOVES]`, where the options
+/// are `--report=human|json`, `--seed=N` and `--move-time=SECONDS`.
+///
+/// e.g. `board_games hard 9x6x5` for Five-in-a-Row, or `board_games hard 4453` to carry on from
+/// the standard board after those 1-based columns have been played.
+///
+/// The bot plays from `opening.book` in the working directory when there is one. Build it with
+/// the `build_book` binary. It searches its moves on several cores at once, and keeps
+/// searching while you think.
+/// With `--report`, what it found on each search is written to stderr as text or as JSON lines.
+/// With `--move-time`, it plays the best move it has found once that many seconds are up, on top
+/// of the limits of its difficulty.
+///
+/// The bot's random choices come from `--seed`, or a seed of its own, which is printed with the
+/// moves at the end. With `--seed` and no `--move-time`, the bot searches on one core and doesn't
+/// search while you think, so the same seed and moves replay the game. Otherwise its moves also
+/// depend on timing.
+///
+/// Ctrl-C makes the bot play the best move it has found straight away. Pressed again before the
+/// bot's next search, it quits.
 fn main() -> Result<()> {
+    let (flags, positional): (Vec<_>, Vec<_>) = std::env::args()
+        .skip(1)
+        .partition(|arg| arg.starts_with("--"));
+    let mut args = positional.into_iter();
+
+    let difficulty = match args.next() {
+        Some(arg) => arg.parse()?,
+        None => Difficulty::default(),
+    };
+
+    let board = match args.next() {
+        Some(arg) => parse_board(&arg)?,
+        None => Board::new(),
+    };
+
+    println!("Playing against a {difficulty} bot.");
+
     let yellow_player = Box::new(player::Human {});
-    let red_player = Box::new(player::Bot::new(Square::Red));
+    // Threads and pondering both make the moves depend on timing, which a seed can't replay.
+    let seeded = flags.iter().any(|flag| flag.starts_with("--seed="));
+    let threads = if seeded {
+        1
+    } else {
+        std::thread::available_parallelism().map_or(1, |n| n.get())
+    };
+    let mut config = BotConfig::new(difficulty)
+        .with_threads(threads)
+        .with_pondering(!seeded);
+    for flag in &flags {
+        if let Some(seconds) = flag.strip_prefix("--move-time=") {
+            config = config.with_move_time(Duration::try_from_secs_f64(seconds.parse()?)?);
+        }
+    }
+    let mut bot = player::Bot::with_config(Square::Red, config);
+
+    match OpeningBook::load(BOOK_PATH) {
+        Ok(book) => bot = bot.with_book(book),
+        Err(BookError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => eprintln!("Not using {BOOK_PATH}: {e}"),
+    }
+
+    for flag in flags {
+        bot = match flag.as_str() {
+            "--report=human" => bot.with_report_sink(HumanReadable::new(std::io::stderr())),
+            "--report=json" => bot.with_report_sink(JsonLines::new(std::io::stderr())),
+            _ if flag.starts_with("--seed=") => bot.with_seed(flag["--seed=".len()..].parse()?),
+            _ if flag.starts_with("--move-time=") => bot,
+            _ => return Err(anyhow!("Unknown option \"{flag}\".")),
+        };
+    }
+
+    let stop = StopToken::new();
+    bot = bot.with_stop_token(stop.clone());
+    ctrlc::set_handler(move || {
+        if stop.is_stopped() {
+            std::process::exit(130);
+        }
+        stop.stop();
+        eprintln!("Press Ctrl-C again to quit.");
+    })?;
 
-    let mut game = Game::new(yellow_player, red_player);
+    let red_player = Box::new(bot);
+
+    let mut game = Game::with_board(yellow_player, red_player, board);
 
     game.game_loop()?;
 
     Ok(())
 }
+
+fn parse_board(arg: &str) -> Result<Board> {
+    if !arg.contains('x') {
+        let board = Board::from_moves(arg)?;
+
+        // There would be no moves left to play.
+        if board.check_for_win().is_some() || board.is_full() {
+            return Err(anyhow!("The game is already over after \"{arg}\"."));
+        }
+
+        return Ok(board);
+    }
+
+    let dimensions = arg
+        .split('x')
+        .map(|d| d.parse::<usize>())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match dimensions[..] {
+        [width, height, connect_n] => Ok(Board::with_dimensions(width, height, connect_n)?),
+        _ => Err(anyhow!(
+            "Expected the board as WIDTHxHEIGHTxCONNECT, got \"{arg}\"."
+        )),
+    }
+}